@@ -0,0 +1,59 @@
+use std::hint::black_box;
+use std::time::Duration;
+use std::time::Instant;
+
+use rt_own::Holder;
+use rt_own::Owner;
+use rt_own::Pool;
+
+const ROUND: usize = 20;
+const LEN: usize = 100_000;
+
+struct Node {
+    _value: u64,
+    _next: Option<Holder<Node>>,
+}
+
+fn main() {
+    let global = bench(|| {
+        let nodes: Vec<_> = (0 .. LEN).map(|i| Holder::new(node(i))).collect();
+        drop(black_box(nodes));
+    });
+    let pool = Pool::new();
+    let pooled = bench(|| {
+        let nodes: Vec<_> = (0 .. LEN).map(|i| pool.holder(node(i))).collect();
+        drop(black_box(nodes));
+    });
+    report("holder/global", global);
+    report("holder/pool", pooled);
+
+    let global = bench(|| {
+        for i in 0 .. LEN {
+            drop(black_box(Owner::new(node(i))));
+        }
+    });
+    let pooled = bench(|| {
+        for i in 0 .. LEN {
+            drop(black_box(pool.owner(node(i))));
+        }
+    });
+    report("owner/global", global);
+    report("owner/pool", pooled);
+}
+
+fn node(i: usize) -> Node {
+    Node { _value: i as u64, _next: None }
+}
+
+fn bench(mut f: impl FnMut()) -> Duration {
+    f();
+    let start = Instant::now();
+    for _ in 0 .. ROUND {
+        f();
+    }
+    start.elapsed() / ROUND as u32
+}
+
+fn report(name: &str, duration: Duration) {
+    println!("{name:16} {:>10.3?} per {LEN} allocations", duration);
+}
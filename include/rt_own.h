@@ -0,0 +1,170 @@
+#ifndef RT_OWN_H
+#define RT_OWN_H
+
+/* generated by cbindgen from src/ffi.rs, don't edit it manually */
+
+#include <stdbool.h>
+#include <stddef.h>
+
+typedef enum {
+  RT_OWN_ERROR_OK = 0,
+  RT_OWN_ERROR_NULL_POINTER = 1,
+  RT_OWN_ERROR_DROPPED = 2,
+  RT_OWN_ERROR_OWNED = 3,
+  RT_OWN_ERROR_VIEWED = 4,
+  RT_OWN_ERROR_FROZEN = 5,
+  RT_OWN_ERROR_NOT_DROPPED = 6,
+} RtOwnError;
+
+/**
+ * A holder of foreign data.
+ *
+ * Counts of handles aren't atomic, so every handle made from a holder must be used and released
+ * on the thread which created the holder.
+ */
+typedef struct RtOwnHolder RtOwnHolder;
+
+/**
+ * An owner of foreign data, which must be used and released on the thread which created its holder.
+ */
+typedef struct RtOwnOwner RtOwnOwner;
+
+/**
+ * A viewer of foreign data, which must be used and released on the thread which created its holder.
+ */
+typedef struct RtOwnViewer RtOwnViewer;
+
+typedef void (*RtOwnDrop)(void*);
+
+typedef struct {
+  bool dropped;
+  bool owned;
+  bool frozen;
+  size_t holder_count;
+  size_t viewer_count;
+} RtOwnState;
+
+/**
+ * Creates a holder of `data`, `drop` is called with `data` when the data is dropped.
+ *
+ * # Safety
+ *
+ * `drop` must be safe to be called with `data` once.
+ */
+RtOwnHolder *rt_own_holder_new(void *data, RtOwnDrop drop);
+
+/**
+ * Returns a new holder of the same data.
+ *
+ * # Safety
+ *
+ * `holder` must be null or a living holder.
+ */
+RtOwnHolder *rt_own_holder_clone(RtOwnHolder *holder);
+
+/**
+ * # Safety
+ *
+ * `holder` must be null or a living holder, which can't be used after this call.
+ */
+void rt_own_holder_release(RtOwnHolder *holder);
+
+/**
+ * # Safety
+ *
+ * `holder` must be null or a living holder, and `state` must be null or valid for writes.
+ */
+RtOwnError rt_own_holder_state(RtOwnHolder *holder, RtOwnState *state);
+
+/**
+ * Reinitializes dropped data with `data` and `drop`, which are not used when it fails.
+ *
+ * # Safety
+ *
+ * `holder` must be null or a living holder, and `drop` must be safe to be called with `data` once.
+ */
+RtOwnError rt_own_holder_reinit(RtOwnHolder *holder, void *data, RtOwnDrop drop);
+
+/**
+ * Acquires an owner and writes it to `owner` when it succeeds.
+ *
+ * # Safety
+ *
+ * `holder` must be null or a living holder, and `owner` must be null or valid for writes.
+ */
+RtOwnError rt_own_owner_acquire(RtOwnHolder *holder, RtOwnOwner **owner);
+
+/**
+ * # Safety
+ *
+ * `owner` must be null or a living owner, which can't be used after this call.
+ */
+void rt_own_owner_release(RtOwnOwner *owner);
+
+/**
+ * Returns a new holder of the data of `owner`.
+ *
+ * # Safety
+ *
+ * `owner` must be null or a living owner.
+ */
+RtOwnHolder *rt_own_owner_holder(RtOwnOwner *owner);
+
+/**
+ * # Safety
+ *
+ * `owner` must be null or a living owner.
+ */
+void *rt_own_owner_data(RtOwnOwner *owner);
+
+/**
+ * Drops the data of `owner`, which is released.
+ *
+ * # Safety
+ *
+ * `owner` must be null or a living owner, which can't be used after this call.
+ */
+void rt_own_owner_drop_data(RtOwnOwner *owner);
+
+/**
+ * Acquires a viewer and writes it to `viewer` when it succeeds.
+ *
+ * # Safety
+ *
+ * `holder` must be null or a living holder, and `viewer` must be null or valid for writes.
+ */
+RtOwnError rt_own_viewer_acquire(RtOwnHolder *holder, RtOwnViewer **viewer);
+
+/**
+ * Returns a new viewer of the same data.
+ *
+ * # Safety
+ *
+ * `viewer` must be null or a living viewer.
+ */
+RtOwnViewer *rt_own_viewer_clone(RtOwnViewer *viewer);
+
+/**
+ * # Safety
+ *
+ * `viewer` must be null or a living viewer, which can't be used after this call.
+ */
+void rt_own_viewer_release(RtOwnViewer *viewer);
+
+/**
+ * Returns a new holder of the data of `viewer`.
+ *
+ * # Safety
+ *
+ * `viewer` must be null or a living viewer.
+ */
+RtOwnHolder *rt_own_viewer_holder(RtOwnViewer *viewer);
+
+/**
+ * # Safety
+ *
+ * `viewer` must be null or a living viewer.
+ */
+const void *rt_own_viewer_data(RtOwnViewer *viewer);
+
+#endif  /* RT_OWN_H */
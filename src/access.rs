@@ -0,0 +1,157 @@
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::mem;
+
+use crate::Holder;
+use crate::Owner;
+use crate::State;
+use crate::Viewer;
+use crate::ptr::Ptr;
+
+// a handle whose access level can be changed at runtime
+pub struct Access<D: ?Sized> {
+    ptr: Ptr<D>,
+    level: AccessLevel,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AccessLevel {
+    Holder,
+    Viewer,
+    Owner,
+}
+
+impl<D: ?Sized> Access<D> {
+    pub fn new(data: D) -> Self
+    where D: Sized {
+        Self { ptr: Ptr::new_owner(data), level: AccessLevel::Owner }
+    }
+
+    pub fn state(&self) -> State {
+        self.ptr.cell().state()
+    }
+
+    pub fn level(&self) -> AccessLevel {
+        self.level
+    }
+
+    // when failed, the level is unchanged
+    pub fn upgrade_to_owner(&mut self) -> Result<(), State> {
+        match self.level {
+            AccessLevel::Holder => self.ptr.cell().holder_to_owner()?,
+            AccessLevel::Viewer => self.ptr.cell().viewer_to_owner()?,
+            AccessLevel::Owner => return Ok(()),
+        }
+        self.level = AccessLevel::Owner;
+        Ok(())
+    }
+
+    // a holder can only be changed to a viewer when data can be viewed
+    // when failed, the level is unchanged
+    pub fn downgrade_to_viewer(&mut self) -> Result<(), State> {
+        match self.level {
+            AccessLevel::Holder => self.ptr.cell().holder_to_viewer()?,
+            AccessLevel::Viewer => return Ok(()),
+            AccessLevel::Owner => self.ptr.cell().owner_to_viewer(),
+        }
+        self.level = AccessLevel::Viewer;
+        Ok(())
+    }
+
+    pub fn downgrade_to_holder(&mut self) {
+        match self.level {
+            AccessLevel::Holder => return,
+            AccessLevel::Viewer => self.ptr.cell().viewer_to_holder(),
+            AccessLevel::Owner => self.ptr.cell().owner_to_holder(),
+        }
+        self.level = AccessLevel::Holder;
+    }
+
+    pub fn try_get(&self) -> Result<&D, State> {
+        if self.level == AccessLevel::Holder {
+            return Err(self.state());
+        }
+        // SAFETY: we are a viewer or an owner, so data hasn't been dropped and there is no mut ref
+        Ok(unsafe { self.ptr.cell().deref() })
+    }
+
+    pub fn try_get_mut(&mut self) -> Result<&mut D, State> {
+        if self.level != AccessLevel::Owner {
+            return Err(self.state());
+        }
+        self.ptr.cell().modify();
+        // SAFETY: we have exclusive ref and data hasn't been dropped
+        Ok(unsafe { self.ptr.cell().deref_mut() })
+    }
+
+    pub(crate) fn ptr(&self) -> &Ptr<D> {
+        &self.ptr
+    }
+}
+
+impl<D: ?Sized> Drop for Access<D> {
+    fn drop(&mut self) {
+        match self.level {
+            AccessLevel::Holder => self.ptr.drop_from_holder(),
+            AccessLevel::Viewer => self.ptr.drop_from_viewer(),
+            AccessLevel::Owner => self.ptr.drop_from_owner(),
+        }
+    }
+}
+
+impl<D: ?Sized> From<Holder<D>> for Access<D> {
+    fn from(value: Holder<D>) -> Self {
+        Self { ptr: Holder::ptr(&value).clone_to_holder(), level: AccessLevel::Holder }
+    }
+}
+
+impl<D: ?Sized> From<Viewer<D>> for Access<D> {
+    fn from(value: Viewer<D>) -> Self {
+        let holder = Holder::from(value);
+        let ptr = Holder::ptr(&holder).clone_to_viewer().unwrap();
+        Self { ptr, level: AccessLevel::Viewer }
+    }
+}
+
+impl<D: ?Sized> From<Owner<D>> for Access<D> {
+    fn from(value: Owner<D>) -> Self {
+        let ptr = Owner::ptr(&value).transfer();
+        mem::forget(value);
+        Self { ptr, level: AccessLevel::Owner }
+    }
+}
+
+impl<D: ?Sized> Debug for Access<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Access").field("ptr", &self.ptr).field("level", &self.level).finish()
+    }
+}
+
+impl<D: ?Sized> PartialEq for Access<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr
+    }
+}
+
+impl<D: ?Sized> Eq for Access<D> {}
+
+impl<D: ?Sized> Hash for Access<D> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ptr.hash(state);
+    }
+}
+
+impl<D: ?Sized> PartialOrd for Access<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<D: ?Sized> Ord for Access<D> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ptr.cmp(&other.ptr)
+    }
+}
@@ -0,0 +1,264 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+
+use crate::Frozen;
+use crate::Holder;
+use crate::Owner;
+use crate::OwnerRef;
+use crate::State;
+use crate::Viewer;
+use crate::ViewerRef;
+
+// a set of handles, which can be sent to another thread
+// when it contains every outstanding handle of every allocation in it
+#[derive(Default)]
+pub struct HandleBundle {
+    handles: Vec<Box<dyn BundleItem>>,
+}
+
+pub struct SendHandleBundle {
+    bundle: HandleBundle,
+}
+
+// SAFETY:
+// the bundle contains every handle of its allocations and the data of them are Send
+// no handle can be made or accessed without converting back to HandleBundle
+// so the states and the data can only be accessed from the thread that has self
+unsafe impl Send for SendHandleBundle {}
+
+/// Data of handles must be `Send`, and handles aren't `Send`, so data containing handles can't be bundled.
+/// To move a subgraph, bundle the handles of its leaves and rebuild the nodes containing handles on the other thread.
+///
+/// ```compile_fail
+/// use rt_own::HandleBundle;
+/// use rt_own::Holder;
+///
+/// let inner = Holder::new(0);
+/// let mut bundle = HandleBundle::new();
+/// bundle.push(Holder::new(Holder::clone(&inner)));
+/// bundle.push(inner);
+/// ```
+pub trait BundleHandle: BundleItem {}
+
+pub trait BundleItem: Any {
+    fn addr(&self) -> *const ();
+    fn state(&self) -> State;
+    fn kind(&self) -> Kind;
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Kind {
+    Holder,
+    Viewer,
+    Owner,
+}
+
+#[derive(Default)]
+struct Count {
+    holder: usize,
+    viewer: usize,
+    owner: usize,
+}
+
+impl HandleBundle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push<H: BundleHandle>(&mut self, handle: H) {
+        self.handles.push(Box::new(handle));
+    }
+
+    pub fn take<H: BundleHandle>(&mut self) -> Option<H> {
+        let i = self.handles.iter().position(|h| (&**h as &dyn Any).is::<H>())?;
+        let handle: Box<dyn Any> = self.handles.swap_remove(i);
+        Some(*handle.downcast::<H>().unwrap())
+    }
+
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        let mut counts: BTreeMap<*const (), (State, Count)> = BTreeMap::new();
+        for handle in &self.handles {
+            let (_, count) =
+                counts.entry(handle.addr()).or_insert((handle.state(), Count::default()));
+            match handle.kind() {
+                Kind::Holder => count.holder += 1,
+                Kind::Viewer => count.viewer += 1,
+                Kind::Owner => count.owner += 1,
+            }
+        }
+        counts.values().all(|(state, count)| {
+            !state.is_local()
+                && state.holder_count() == count.holder
+                && state.viewer_count() == count.viewer
+                && state.is_owned() == (count.owner == 1)
+        })
+    }
+
+    pub fn try_into_send(self) -> Result<SendHandleBundle, Self> {
+        if self.is_complete() { Ok(SendHandleBundle { bundle: self }) } else { Err(self) }
+    }
+}
+
+impl From<SendHandleBundle> for HandleBundle {
+    fn from(value: SendHandleBundle) -> Self {
+        value.bundle
+    }
+}
+
+impl TryFrom<HandleBundle> for SendHandleBundle {
+    type Error = HandleBundle;
+    fn try_from(value: HandleBundle) -> Result<Self, Self::Error> {
+        value.try_into_send()
+    }
+}
+
+impl Debug for HandleBundle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HandleBundle").field("len", &self.len()).finish()
+    }
+}
+
+impl Debug for SendHandleBundle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SendHandleBundle").field("len", &self.bundle.len()).finish()
+    }
+}
+
+impl<D: ?Sized + Send + 'static> BundleHandle for Holder<D> {}
+
+impl<D: ?Sized + Send + 'static> BundleItem for Holder<D> {
+    fn addr(&self) -> *const () {
+        Holder::ptr(self).addr()
+    }
+
+    fn state(&self) -> State {
+        Holder::state(self)
+    }
+
+    fn kind(&self) -> Kind {
+        Kind::Holder
+    }
+}
+
+impl<D: ?Sized + Send + 'static> BundleHandle for Viewer<D> {}
+
+impl<D: ?Sized + Send + 'static> BundleItem for Viewer<D> {
+    fn addr(&self) -> *const () {
+        Viewer::ptr(self).addr()
+    }
+
+    fn state(&self) -> State {
+        Viewer::state(self)
+    }
+
+    fn kind(&self) -> Kind {
+        Kind::Viewer
+    }
+}
+
+impl<D: ?Sized + Send + 'static> BundleHandle for Frozen<D> {}
+
+impl<D: ?Sized + Send + 'static> BundleItem for Frozen<D> {
+    fn addr(&self) -> *const () {
+        Frozen::ptr(self).addr()
+    }
+
+    fn state(&self) -> State {
+        Frozen::state(self)
+    }
+
+    fn kind(&self) -> Kind {
+        Kind::Viewer
+    }
+}
+
+impl<D: ?Sized + Send + 'static> BundleHandle for Owner<D> {}
+
+impl<D: ?Sized + Send + 'static> BundleItem for Owner<D> {
+    fn addr(&self) -> *const () {
+        Owner::ptr(self).addr()
+    }
+
+    fn state(&self) -> State {
+        Owner::state(self)
+    }
+
+    fn kind(&self) -> Kind {
+        Kind::Owner
+    }
+}
+
+/// The target may be out of the allocation and shared with other threads, so it must be `Sync`.
+///
+/// ```compile_fail
+/// use std::cell::Cell;
+///
+/// use rt_own::HandleBundle;
+/// use rt_own::Viewer;
+/// use rt_own::ViewerRef;
+///
+/// let cell: &'static Cell<i32> = Box::leak(Box::new(Cell::new(0)));
+/// let viewer = ViewerRef::map(ViewerRef::from(Viewer::new(())), |_| cell);
+/// HandleBundle::new().push(viewer);
+/// ```
+impl<Source, Target> BundleHandle for ViewerRef<Source, Target>
+where
+    Source: ?Sized + Send + 'static,
+    Target: ?Sized + Sync + 'static,
+{
+}
+
+impl<Source, Target> BundleItem for ViewerRef<Source, Target>
+where
+    Source: ?Sized + Send + 'static,
+    Target: ?Sized + Sync + 'static,
+{
+    fn addr(&self) -> *const () {
+        ViewerRef::source(self).addr()
+    }
+
+    fn state(&self) -> State {
+        ViewerRef::state(self)
+    }
+
+    fn kind(&self) -> Kind {
+        Kind::Viewer
+    }
+}
+
+impl<Source, Target> BundleHandle for OwnerRef<Source, Target>
+where
+    Source: ?Sized + Send + 'static,
+    Target: ?Sized + Send + 'static,
+{
+}
+
+impl<Source, Target> BundleItem for OwnerRef<Source, Target>
+where
+    Source: ?Sized + Send + 'static,
+    Target: ?Sized + Send + 'static,
+{
+    fn addr(&self) -> *const () {
+        OwnerRef::source(self).addr()
+    }
+
+    fn state(&self) -> State {
+        OwnerRef::state(self)
+    }
+
+    fn kind(&self) -> Kind {
+        Kind::Owner
+    }
+}
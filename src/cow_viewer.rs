@@ -0,0 +1,114 @@
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::ops::Deref;
+
+use crate::Holder;
+use crate::State;
+use crate::Viewer;
+use crate::ptr::Ptr;
+
+// a viewer which clones data into a new allocation when it needs to modify shared data
+pub struct CowViewer<D: ?Sized> {
+    ptr: Ptr<D>,
+}
+
+impl<D: ?Sized> CowViewer<D> {
+    pub fn new(data: D) -> Self
+    where D: Sized {
+        Self { ptr: Ptr::new_viewer(data) }
+    }
+
+    pub fn state(viewer: &Self) -> State {
+        viewer.ptr.cell().state()
+    }
+
+    // modify data in place when it is the only handle, otherwise detach from the shared data
+    pub fn make_mut(viewer: &mut Self) -> &mut D
+    where D: Clone {
+        if !Self::is_unique(viewer) {
+            let data = D::clone(viewer);
+            viewer.ptr.drop_from_viewer();
+            viewer.ptr = Ptr::new_viewer(data);
+        }
+        viewer.ptr.cell().modify();
+        // SAFETY: we are the only handle and we have exclusive ref
+        unsafe { viewer.ptr.cell().deref_mut() }
+    }
+
+    pub(crate) fn ptr(viewer: &Self) -> &Ptr<D> {
+        &viewer.ptr
+    }
+
+    fn is_unique(viewer: &Self) -> bool {
+        let state = Self::state(viewer);
+        state.viewer_count() == 1 && state.holder_count() == 0 && !state.is_frozen()
+    }
+}
+
+impl<D: ?Sized> Deref for CowViewer<D> {
+    type Target = D;
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: when self is alive there is no owner and data hasn't been dropped
+        unsafe { self.ptr.cell().deref() }
+    }
+}
+
+impl<D: ?Sized> Clone for CowViewer<D> {
+    fn clone(&self) -> Self {
+        Self { ptr: self.ptr.clone_to_viewer().unwrap() }
+    }
+}
+
+impl<D: ?Sized> Drop for CowViewer<D> {
+    fn drop(&mut self) {
+        self.ptr.drop_from_viewer();
+    }
+}
+
+impl<D: ?Sized> From<Viewer<D>> for CowViewer<D> {
+    fn from(value: Viewer<D>) -> Self {
+        let holder = Holder::from(value);
+        Self { ptr: Holder::ptr(&holder).clone_to_viewer().unwrap() }
+    }
+}
+
+impl<D: ?Sized> Debug for CowViewer<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("CowViewer").field(&self.ptr).finish()
+    }
+}
+
+impl<D: Default> Default for CowViewer<D> {
+    fn default() -> Self {
+        Self::new(D::default())
+    }
+}
+
+impl<D: ?Sized> PartialEq for CowViewer<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr
+    }
+}
+
+impl<D: ?Sized> Eq for CowViewer<D> {}
+
+impl<D: ?Sized> Hash for CowViewer<D> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ptr.hash(state);
+    }
+}
+
+impl<D: ?Sized> PartialOrd for CowViewer<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<D: ?Sized> Ord for CowViewer<D> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ptr.cmp(&other.ptr)
+    }
+}
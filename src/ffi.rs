@@ -0,0 +1,362 @@
+use core::ffi::c_void;
+use core::mem::ManuallyDrop;
+use core::ptr;
+
+use crate::Holder;
+use crate::Owner;
+use crate::State;
+use crate::Viewer;
+use crate::ptr::Ptr;
+
+/// A holder of foreign data.
+///
+/// Counts of handles aren't atomic, so every handle made from a holder must be used and released
+/// on the thread which created the holder.
+pub struct RtOwnHolder {
+    _private: [u8; 0],
+}
+
+/// A viewer of foreign data, which must be used and released on the thread which created its holder.
+pub struct RtOwnViewer {
+    _private: [u8; 0],
+}
+
+/// An owner of foreign data, which must be used and released on the thread which created its holder.
+pub struct RtOwnOwner {
+    _private: [u8; 0],
+}
+
+pub type RtOwnDrop = Option<unsafe extern "C" fn(*mut c_void)>;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RtOwnError {
+    Ok = 0,
+    NullPointer = 1,
+    Dropped = 2,
+    Owned = 3,
+    Viewed = 4,
+    Frozen = 5,
+    NotDropped = 6,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RtOwnState {
+    pub dropped: bool,
+    pub owned: bool,
+    pub frozen: bool,
+    pub holder_count: usize,
+    pub viewer_count: usize,
+}
+
+struct Payload {
+    data: *mut c_void,
+    drop: RtOwnDrop,
+}
+
+impl Drop for Payload {
+    fn drop(&mut self) {
+        if let Some(drop) = self.drop {
+            // SAFETY: the caller promises that drop can be called with data once
+            unsafe { drop(self.data) }
+        }
+    }
+}
+
+impl From<State> for RtOwnError {
+    fn from(state: State) -> Self {
+        if state.is_dropped() {
+            RtOwnError::Dropped
+        } else if state.is_owned() {
+            RtOwnError::Owned
+        } else if state.is_frozen() {
+            RtOwnError::Frozen
+        } else {
+            RtOwnError::Viewed
+        }
+    }
+}
+
+impl From<State> for RtOwnState {
+    fn from(state: State) -> Self {
+        RtOwnState {
+            dropped: state.is_dropped(),
+            owned: state.is_owned(),
+            frozen: state.is_frozen(),
+            holder_count: state.holder_count(),
+            viewer_count: state.viewer_count(),
+        }
+    }
+}
+
+/// Creates a holder of `data`, `drop` is called with `data` when the data is dropped.
+///
+/// # Safety
+///
+/// `drop` must be safe to be called with `data` once.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_holder_new(data: *mut c_void, drop: RtOwnDrop) -> *mut RtOwnHolder {
+    Holder::into_raw(Holder::new(Payload { data, drop })).cast_mut().cast()
+}
+
+/// Returns a new holder of the same data.
+///
+/// # Safety
+///
+/// `holder` must be null or a living holder.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_holder_clone(holder: *mut RtOwnHolder) -> *mut RtOwnHolder {
+    if !holder.is_null() {
+        // SAFETY: holder is a living holder
+        unsafe {
+            Holder::increment_count(holder.cast_const().cast::<Payload>());
+        }
+    }
+    holder
+}
+
+/// # Safety
+///
+/// `holder` must be null or a living holder, which can't be used after this call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_holder_release(holder: *mut RtOwnHolder) {
+    if !holder.is_null() {
+        // SAFETY: holder is a living holder
+        unsafe {
+            Holder::decrement_count(holder.cast_const().cast::<Payload>());
+        }
+    }
+}
+
+/// # Safety
+///
+/// `holder` must be null or a living holder, and `state` must be null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_holder_state(
+    holder: *mut RtOwnHolder, state: *mut RtOwnState,
+) -> RtOwnError {
+    if holder.is_null() || state.is_null() {
+        return RtOwnError::NullPointer;
+    }
+    // SAFETY: holder is a living holder
+    let holder = unsafe { borrow_holder(holder) };
+    // SAFETY: state is valid for writes
+    unsafe {
+        state.write(RtOwnState::from(Holder::state(&holder)));
+    }
+    RtOwnError::Ok
+}
+
+/// Reinitializes dropped data with `data` and `drop`, which are not used when it fails.
+///
+/// # Safety
+///
+/// `holder` must be null or a living holder, and `drop` must be safe to be called with `data` once.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_holder_reinit(
+    holder: *mut RtOwnHolder, data: *mut c_void, drop: RtOwnDrop,
+) -> RtOwnError {
+    if holder.is_null() {
+        return RtOwnError::NullPointer;
+    }
+    // SAFETY: holder is a living holder
+    let holder = unsafe { borrow_holder(holder) };
+    let state = Holder::state(&holder);
+    if state.is_frozen() {
+        return RtOwnError::Frozen;
+    }
+    if !state.is_dropped() {
+        return RtOwnError::NotDropped;
+    }
+    match Holder::reinit(&holder, Payload { data, drop }) {
+        Ok(()) => RtOwnError::Ok,
+        Err(state) => RtOwnError::from(state),
+    }
+}
+
+/// Acquires an owner and writes it to `owner` when it succeeds.
+///
+/// # Safety
+///
+/// `holder` must be null or a living holder, and `owner` must be null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_owner_acquire(
+    holder: *mut RtOwnHolder, owner: *mut *mut RtOwnOwner,
+) -> RtOwnError {
+    if holder.is_null() || owner.is_null() {
+        return RtOwnError::NullPointer;
+    }
+    // SAFETY: holder is a living holder
+    let holder = unsafe { borrow_holder(holder) };
+    match Owner::try_from(&*holder) {
+        Ok(o) => {
+            // SAFETY: owner is valid for writes
+            unsafe {
+                owner.write(Owner::into_raw(o).cast());
+            }
+            RtOwnError::Ok
+        }
+        Err(state) => RtOwnError::from(state),
+    }
+}
+
+/// # Safety
+///
+/// `owner` must be null or a living owner, which can't be used after this call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_owner_release(owner: *mut RtOwnOwner) {
+    if !owner.is_null() {
+        // SAFETY: owner is a living owner
+        drop(unsafe { Owner::from_raw(owner.cast::<Payload>()) });
+    }
+}
+
+/// Returns a new holder of the data of `owner`.
+///
+/// # Safety
+///
+/// `owner` must be null or a living owner.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_owner_holder(owner: *mut RtOwnOwner) -> *mut RtOwnHolder {
+    if owner.is_null() {
+        return ptr::null_mut();
+    }
+    // SAFETY: owner is a living owner
+    unsafe {
+        increment_holder_count(owner.cast_const().cast::<Payload>());
+    }
+    owner.cast()
+}
+
+/// # Safety
+///
+/// `owner` must be null or a living owner.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_owner_data(owner: *mut RtOwnOwner) -> *mut c_void {
+    if owner.is_null() {
+        return ptr::null_mut();
+    }
+    // SAFETY: owner is a living owner
+    let mut owner = unsafe { borrow_owner(owner) };
+    // the data may be modified through the returned ptr
+    let payload: &mut Payload = &mut owner;
+    payload.data
+}
+
+/// Drops the data of `owner`, which is released.
+///
+/// # Safety
+///
+/// `owner` must be null or a living owner, which can't be used after this call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_owner_drop_data(owner: *mut RtOwnOwner) {
+    if !owner.is_null() {
+        // SAFETY: owner is a living owner
+        Owner::drop_data(unsafe { Owner::from_raw(owner.cast::<Payload>()) });
+    }
+}
+
+/// Acquires a viewer and writes it to `viewer` when it succeeds.
+///
+/// # Safety
+///
+/// `holder` must be null or a living holder, and `viewer` must be null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_viewer_acquire(
+    holder: *mut RtOwnHolder, viewer: *mut *mut RtOwnViewer,
+) -> RtOwnError {
+    if holder.is_null() || viewer.is_null() {
+        return RtOwnError::NullPointer;
+    }
+    // SAFETY: holder is a living holder
+    let holder = unsafe { borrow_holder(holder) };
+    match Viewer::try_from(&*holder) {
+        Ok(v) => {
+            // SAFETY: viewer is valid for writes
+            unsafe {
+                viewer.write(Viewer::into_raw(v).cast_mut().cast());
+            }
+            RtOwnError::Ok
+        }
+        Err(state) => RtOwnError::from(state),
+    }
+}
+
+/// Returns a new viewer of the same data.
+///
+/// # Safety
+///
+/// `viewer` must be null or a living viewer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_viewer_clone(viewer: *mut RtOwnViewer) -> *mut RtOwnViewer {
+    if !viewer.is_null() {
+        // SAFETY: viewer is a living viewer
+        unsafe {
+            Viewer::increment_count(viewer.cast_const().cast::<Payload>());
+        }
+    }
+    viewer
+}
+
+/// # Safety
+///
+/// `viewer` must be null or a living viewer, which can't be used after this call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_viewer_release(viewer: *mut RtOwnViewer) {
+    if !viewer.is_null() {
+        // SAFETY: viewer is a living viewer
+        unsafe {
+            Viewer::decrement_count(viewer.cast_const().cast::<Payload>());
+        }
+    }
+}
+
+/// Returns a new holder of the data of `viewer`.
+///
+/// # Safety
+///
+/// `viewer` must be null or a living viewer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_viewer_holder(viewer: *mut RtOwnViewer) -> *mut RtOwnHolder {
+    if viewer.is_null() {
+        return ptr::null_mut();
+    }
+    // SAFETY: viewer is a living viewer
+    unsafe {
+        increment_holder_count(viewer.cast_const().cast::<Payload>());
+    }
+    viewer.cast()
+}
+
+/// # Safety
+///
+/// `viewer` must be null or a living viewer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rt_own_viewer_data(viewer: *mut RtOwnViewer) -> *const c_void {
+    if viewer.is_null() {
+        return ptr::null();
+    }
+    // SAFETY: viewer is a living viewer
+    unsafe { (*viewer.cast::<Payload>()).data }
+}
+
+// SAFETY: holder is a living holder
+unsafe fn borrow_holder(holder: *mut RtOwnHolder) -> ManuallyDrop<Holder<Payload>> {
+    // SAFETY: a living holder is made by Holder::into_raw
+    ManuallyDrop::new(unsafe { Holder::from_raw(holder.cast_const().cast::<Payload>()) })
+}
+
+// the new holder count is taken over by the returned holder
+// SAFETY: data is the data ptr of a living handle of any kind
+unsafe fn increment_holder_count(data: *const Payload) {
+    // SAFETY: data is the data ptr of a StateCell
+    let ptr = unsafe { Ptr::from_data_ptr(data, align_of::<Payload>()) };
+    ptr.clone_to_holder();
+}
+
+// SAFETY: owner is a living owner
+unsafe fn borrow_owner(owner: *mut RtOwnOwner) -> ManuallyDrop<Owner<Payload>> {
+    // SAFETY: a living owner is made by Owner::into_raw
+    ManuallyDrop::new(unsafe { Owner::from_raw(owner.cast::<Payload>()) })
+}
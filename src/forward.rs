@@ -0,0 +1,330 @@
+use core::cmp::Ordering;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::ops::Index;
+use core::ops::IndexMut;
+use core::pin::Pin;
+use core::task::Context;
+use core::task::Poll;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::io::Read;
+#[cfg(feature = "std")]
+use std::io::Write;
+
+use crate::Frozen;
+use crate::Owner;
+use crate::OwnerRef;
+use crate::ReadHandle;
+use crate::UniqueOwner;
+use crate::Viewer;
+use crate::ViewerRef;
+
+// Borrow isn't forwarded: Eq, Ord and Hash of handles compare identities,
+// but Borrow requires them to agree with those of the borrowed data
+
+// compare, order and hash handles by the value of their targets instead of their identities
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ByValue<H>(pub H);
+
+impl<H: ReadHandle> PartialEq for ByValue<H>
+where H::Target: PartialEq
+{
+    fn eq(&self, other: &Self) -> bool {
+        ReadHandle::get(&self.0) == ReadHandle::get(&other.0)
+    }
+}
+
+impl<H: ReadHandle> Eq for ByValue<H> where H::Target: Eq {}
+
+impl<H: ReadHandle> PartialOrd for ByValue<H>
+where H::Target: PartialOrd
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        ReadHandle::get(&self.0).partial_cmp(ReadHandle::get(&other.0))
+    }
+}
+
+impl<H: ReadHandle> Ord for ByValue<H>
+where H::Target: Ord
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        ReadHandle::get(&self.0).cmp(ReadHandle::get(&other.0))
+    }
+}
+
+impl<H: ReadHandle> Hash for ByValue<H>
+where H::Target: Hash
+{
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        ReadHandle::get(&self.0).hash(state);
+    }
+}
+
+impl<D: ?Sized> AsRef<D> for Owner<D> {
+    fn as_ref(&self) -> &D {
+        self
+    }
+}
+
+impl<D: ?Sized> AsRef<D> for Viewer<D> {
+    fn as_ref(&self) -> &D {
+        self
+    }
+}
+
+impl<D: ?Sized> AsRef<D> for Frozen<D> {
+    fn as_ref(&self) -> &D {
+        self
+    }
+}
+
+impl<D: ?Sized> AsRef<D> for UniqueOwner<D> {
+    fn as_ref(&self) -> &D {
+        self
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized> AsRef<Target> for OwnerRef<Source, Target> {
+    fn as_ref(&self) -> &Target {
+        self
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized> AsRef<Target> for ViewerRef<Source, Target> {
+    fn as_ref(&self) -> &Target {
+        self
+    }
+}
+
+impl<D: ?Sized> AsMut<D> for Owner<D> {
+    fn as_mut(&mut self) -> &mut D {
+        self
+    }
+}
+
+impl<D: ?Sized> AsMut<D> for UniqueOwner<D> {
+    fn as_mut(&mut self) -> &mut D {
+        self
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized> AsMut<Target> for OwnerRef<Source, Target> {
+    fn as_mut(&mut self) -> &mut Target {
+        self
+    }
+}
+
+impl<D: ?Sized + Display> Display for Owner<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<D: ?Sized + Display> Display for Viewer<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<D: ?Sized + Display> Display for Frozen<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<D: ?Sized + Display> Display for UniqueOwner<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized + Display> Display for OwnerRef<Source, Target> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized + Display> Display for ViewerRef<Source, Target> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<D: ?Sized + Index<I>, I> Index<I> for Owner<D> {
+    type Output = D::Output;
+    fn index(&self, index: I) -> &Self::Output {
+        (**self).index(index)
+    }
+}
+
+impl<D: ?Sized + Index<I>, I> Index<I> for Viewer<D> {
+    type Output = D::Output;
+    fn index(&self, index: I) -> &Self::Output {
+        (**self).index(index)
+    }
+}
+
+impl<D: ?Sized + Index<I>, I> Index<I> for Frozen<D> {
+    type Output = D::Output;
+    fn index(&self, index: I) -> &Self::Output {
+        (**self).index(index)
+    }
+}
+
+impl<D: ?Sized + Index<I>, I> Index<I> for UniqueOwner<D> {
+    type Output = D::Output;
+    fn index(&self, index: I) -> &Self::Output {
+        (**self).index(index)
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized + Index<I>, I> Index<I> for OwnerRef<Source, Target> {
+    type Output = Target::Output;
+    fn index(&self, index: I) -> &Self::Output {
+        (**self).index(index)
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized + Index<I>, I> Index<I> for ViewerRef<Source, Target> {
+    type Output = Target::Output;
+    fn index(&self, index: I) -> &Self::Output {
+        (**self).index(index)
+    }
+}
+
+impl<D: ?Sized + IndexMut<I>, I> IndexMut<I> for Owner<D> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        (**self).index_mut(index)
+    }
+}
+
+impl<D: ?Sized + IndexMut<I>, I> IndexMut<I> for UniqueOwner<D> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        (**self).index_mut(index)
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized + IndexMut<I>, I> IndexMut<I> for OwnerRef<Source, Target> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        (**self).index_mut(index)
+    }
+}
+
+impl<D: ?Sized + Iterator> Iterator for Owner<D> {
+    type Item = D::Item;
+    fn next(&mut self) -> Option<Self::Item> {
+        (**self).next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (**self).size_hint()
+    }
+}
+
+impl<D: ?Sized + Iterator> Iterator for UniqueOwner<D> {
+    type Item = D::Item;
+    fn next(&mut self) -> Option<Self::Item> {
+        (**self).next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (**self).size_hint()
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized + Iterator> Iterator for OwnerRef<Source, Target> {
+    type Item = Target::Item;
+    fn next(&mut self) -> Option<Self::Item> {
+        (**self).next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (**self).size_hint()
+    }
+}
+
+// handles are pointers, so the data won't be moved when handles are moved
+impl<D: ?Sized> Unpin for Owner<D> {}
+
+impl<D: ?Sized> Unpin for UniqueOwner<D> {}
+
+impl<Source: ?Sized, Target: ?Sized> Unpin for OwnerRef<Source, Target> {}
+
+impl<D: ?Sized + Future + Unpin> Future for Owner<D> {
+    type Output = D::Output;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut **self.get_mut()).poll(cx)
+    }
+}
+
+impl<D: ?Sized + Future + Unpin> Future for UniqueOwner<D> {
+    type Output = D::Output;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut **self.get_mut()).poll(cx)
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized + Future + Unpin> Future for OwnerRef<Source, Target> {
+    type Output = Target::Output;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut **self.get_mut()).poll(cx)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: ?Sized + Read> Read for Owner<D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read(buf)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: ?Sized + Read> Read for UniqueOwner<D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read(buf)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Source: ?Sized, Target: ?Sized + Read> Read for OwnerRef<Source, Target> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read(buf)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: ?Sized + Write> Write for Owner<D> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (**self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: ?Sized + Write> Write for UniqueOwner<D> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (**self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Source: ?Sized, Target: ?Sized + Write> Write for OwnerRef<Source, Target> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (**self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
@@ -0,0 +1,124 @@
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::mem;
+use core::ops::Deref;
+
+use crate::Holder;
+use crate::Owner;
+use crate::State;
+use crate::Viewer;
+use crate::ptr::Ptr;
+
+pub struct Frozen<D: ?Sized> {
+    ptr: Ptr<D>,
+}
+
+impl<D: ?Sized> Frozen<D> {
+    pub fn new(data: D) -> Self
+    where D: Sized {
+        Self::from(Owner::new(data))
+    }
+
+    pub fn state(frozen: &Self) -> State {
+        frozen.ptr.cell().state()
+    }
+
+    pub(crate) fn ptr(frozen: &Self) -> &Ptr<D> {
+        &frozen.ptr
+    }
+}
+
+impl<D: ?Sized> Deref for Frozen<D> {
+    type Target = D;
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: frozen data can never be owned and hasn't been dropped when self is alive
+        unsafe { self.ptr.cell().deref() }
+    }
+}
+
+impl<D: ?Sized> Clone for Frozen<D> {
+    fn clone(&self) -> Self {
+        Self { ptr: self.ptr.clone_to_frozen() }
+    }
+}
+
+impl<D: ?Sized> Drop for Frozen<D> {
+    fn drop(&mut self) {
+        self.ptr.drop_from_viewer();
+    }
+}
+
+impl<D: ?Sized> From<Owner<D>> for Frozen<D> {
+    fn from(value: Owner<D>) -> Self {
+        let ptr = Owner::ptr(&value).transfer();
+        mem::forget(value);
+        ptr.freeze();
+        ptr.cell().owner_to_viewer();
+        Self { ptr }
+    }
+}
+
+impl<D: ?Sized> TryFrom<&Holder<D>> for Frozen<D> {
+    type Error = State;
+    fn try_from(value: &Holder<D>) -> Result<Self, Self::Error> {
+        let state = Holder::state(value);
+        if state.is_frozen() && !state.is_dropped() {
+            Ok(Self { ptr: Holder::ptr(value).clone_to_frozen() })
+        } else {
+            Err(state)
+        }
+    }
+}
+
+impl<D: ?Sized> TryFrom<&Viewer<D>> for Frozen<D> {
+    type Error = State;
+    fn try_from(value: &Viewer<D>) -> Result<Self, Self::Error> {
+        let state = Viewer::state(value);
+        if state.is_frozen() {
+            Ok(Self { ptr: Viewer::ptr(value).clone_to_frozen() })
+        } else {
+            Err(state)
+        }
+    }
+}
+
+impl<D: ?Sized> Debug for Frozen<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Frozen").field(&self.ptr).finish()
+    }
+}
+
+impl<D: Default> Default for Frozen<D> {
+    fn default() -> Self {
+        Self::new(D::default())
+    }
+}
+
+impl<D: ?Sized> PartialEq for Frozen<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr
+    }
+}
+
+impl<D: ?Sized> Eq for Frozen<D> {}
+
+impl<D: ?Sized> Hash for Frozen<D> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ptr.hash(state);
+    }
+}
+
+impl<D: ?Sized> PartialOrd for Frozen<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<D: ?Sized> Ord for Frozen<D> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ptr.cmp(&other.ptr)
+    }
+}
@@ -0,0 +1,273 @@
+use crate::Access;
+use crate::CowViewer;
+use crate::Frozen;
+use crate::Holder;
+use crate::Owner;
+use crate::OwnerRef;
+use crate::State;
+use crate::Viewer;
+use crate::ViewerRef;
+#[cfg(feature = "dirty")]
+use crate::ptr;
+
+// common functions of handles to data of type Source
+pub trait Handle: Sealed {
+    type Source: ?Sized;
+
+    fn state(handle: &Self) -> State;
+
+    fn holder(handle: &Self) -> Holder<Self::Source>;
+
+    // address of the allocation, which identifies the data
+    fn addr(handle: &Self) -> *const ();
+
+    fn alloc_id(handle: &Self) -> AllocId {
+        AllocId { addr: Self::addr(handle).addr() }
+    }
+
+    #[cfg(feature = "dirty")]
+    fn is_dirty(handle: &Self) -> bool {
+        // SAFETY: addr is the addr of the StateCell, which is alive when handle is alive
+        unsafe { ptr::header(Self::addr(handle)) }.is_dirty()
+    }
+
+    #[cfg(feature = "dirty")]
+    fn modification_count(handle: &Self) -> usize {
+        // SAFETY: addr is the addr of the StateCell, which is alive when handle is alive
+        unsafe { ptr::header(Self::addr(handle)) }.modification_count()
+    }
+
+    // whether two handles of any kinds point to the same allocation
+    fn ptr_eq<H: Handle + ?Sized>(handle: &Self, other: &H) -> bool {
+        Self::alloc_id(handle) == H::alloc_id(other)
+    }
+
+    // the same as ptr_eq
+    fn same_allocation<H: Handle + ?Sized>(handle: &Self, other: &H) -> bool {
+        Self::ptr_eq(handle, other)
+    }
+}
+
+// identity of an allocation, which is unique while the allocation is alive
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AllocId {
+    addr: usize,
+}
+
+// handles that can read the target
+pub trait ReadHandle: Handle {
+    type Target: ?Sized;
+
+    fn get(handle: &Self) -> &Self::Target;
+}
+
+// handles that can write the target
+pub trait WriteHandle: ReadHandle {
+    fn get_mut(handle: &mut Self) -> &mut Self::Target;
+}
+
+pub trait Sealed {}
+
+impl<D: ?Sized> Sealed for Holder<D> {}
+
+impl<D: ?Sized> Handle for Holder<D> {
+    type Source = D;
+
+    fn state(handle: &Self) -> State {
+        Holder::state(handle)
+    }
+
+    fn holder(handle: &Self) -> Holder<D> {
+        handle.clone()
+    }
+
+    fn addr(handle: &Self) -> *const () {
+        Holder::ptr(handle).addr()
+    }
+}
+
+impl<D: ?Sized> Sealed for Viewer<D> {}
+
+impl<D: ?Sized> Handle for Viewer<D> {
+    type Source = D;
+
+    fn state(handle: &Self) -> State {
+        Viewer::state(handle)
+    }
+
+    fn holder(handle: &Self) -> Holder<D> {
+        Holder::from(handle)
+    }
+
+    fn addr(handle: &Self) -> *const () {
+        Viewer::ptr(handle).addr()
+    }
+}
+
+impl<D: ?Sized> ReadHandle for Viewer<D> {
+    type Target = D;
+
+    fn get(handle: &Self) -> &D {
+        handle
+    }
+}
+
+impl<D: ?Sized> Sealed for Frozen<D> {}
+
+impl<D: ?Sized> Handle for Frozen<D> {
+    type Source = D;
+
+    fn state(handle: &Self) -> State {
+        Frozen::state(handle)
+    }
+
+    fn holder(handle: &Self) -> Holder<D> {
+        Holder::from(handle)
+    }
+
+    fn addr(handle: &Self) -> *const () {
+        Frozen::ptr(handle).addr()
+    }
+}
+
+impl<D: ?Sized> ReadHandle for Frozen<D> {
+    type Target = D;
+
+    fn get(handle: &Self) -> &D {
+        handle
+    }
+}
+
+impl<D: ?Sized> Sealed for Owner<D> {}
+
+impl<D: ?Sized> Handle for Owner<D> {
+    type Source = D;
+
+    fn state(handle: &Self) -> State {
+        Owner::state(handle)
+    }
+
+    fn holder(handle: &Self) -> Holder<D> {
+        Holder::from(handle)
+    }
+
+    fn addr(handle: &Self) -> *const () {
+        Owner::ptr(handle).addr()
+    }
+}
+
+impl<D: ?Sized> ReadHandle for Owner<D> {
+    type Target = D;
+
+    fn get(handle: &Self) -> &D {
+        handle
+    }
+}
+
+impl<D: ?Sized> WriteHandle for Owner<D> {
+    fn get_mut(handle: &mut Self) -> &mut D {
+        handle
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized> Sealed for ViewerRef<Source, Target> {}
+
+impl<Source: ?Sized, Target: ?Sized> Handle for ViewerRef<Source, Target> {
+    type Source = Source;
+
+    fn state(handle: &Self) -> State {
+        ViewerRef::state(handle)
+    }
+
+    fn holder(handle: &Self) -> Holder<Source> {
+        Holder::from(handle)
+    }
+
+    fn addr(handle: &Self) -> *const () {
+        ViewerRef::source(handle).addr()
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized> ReadHandle for ViewerRef<Source, Target> {
+    type Target = Target;
+
+    fn get(handle: &Self) -> &Target {
+        handle
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized> Sealed for OwnerRef<Source, Target> {}
+
+impl<Source: ?Sized, Target: ?Sized> Handle for OwnerRef<Source, Target> {
+    type Source = Source;
+
+    fn state(handle: &Self) -> State {
+        OwnerRef::state(handle)
+    }
+
+    fn holder(handle: &Self) -> Holder<Source> {
+        Holder::from(handle)
+    }
+
+    fn addr(handle: &Self) -> *const () {
+        OwnerRef::source(handle).addr()
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized> ReadHandle for OwnerRef<Source, Target> {
+    type Target = Target;
+
+    fn get(handle: &Self) -> &Target {
+        handle
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized> WriteHandle for OwnerRef<Source, Target> {
+    fn get_mut(handle: &mut Self) -> &mut Target {
+        handle
+    }
+}
+
+impl<D: ?Sized> Sealed for Access<D> {}
+
+impl<D: ?Sized> Handle for Access<D> {
+    type Source = D;
+
+    fn state(handle: &Self) -> State {
+        handle.state()
+    }
+
+    fn holder(handle: &Self) -> Holder<D> {
+        Holder::from(handle)
+    }
+
+    fn addr(handle: &Self) -> *const () {
+        handle.ptr().addr()
+    }
+}
+
+impl<D: ?Sized> Sealed for CowViewer<D> {}
+
+impl<D: ?Sized> Handle for CowViewer<D> {
+    type Source = D;
+
+    fn state(handle: &Self) -> State {
+        CowViewer::state(handle)
+    }
+
+    fn holder(handle: &Self) -> Holder<D> {
+        Holder::from(handle)
+    }
+
+    fn addr(handle: &Self) -> *const () {
+        CowViewer::ptr(handle).addr()
+    }
+}
+
+impl<D: ?Sized> ReadHandle for CowViewer<D> {
+    type Target = D;
+
+    fn get(handle: &Self) -> &D {
+        handle
+    }
+}
@@ -0,0 +1,191 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::mem;
+use core::ops::Deref;
+use core::ops::DerefMut;
+
+use crate::Holder;
+use crate::Owner;
+use crate::State;
+
+// a revision of data, recorded for every owner acquired by History::edit
+pub trait Revision<D: ?Sized>: Sized {
+    type Base;
+
+    // called when the owner is acquired
+    fn base(data: &D) -> Self::Base;
+
+    // called when the owner is released, return None when data is unchanged
+    fn diff(base: Self::Base, data: &D) -> Option<Self>;
+
+    // restore data and return the revision which reverts the restoration
+    fn revert(self, data: &mut D) -> Self;
+}
+
+// a revision which stores a full copy of data
+#[derive(Clone, Debug)]
+pub struct Snapshot<D> {
+    data: D,
+}
+
+// undo and redo edits of data behind a holder
+// edits made by owners not from the history clear the revisions, which can't be reverted over them
+pub struct History<D: ?Sized, R: Revision<D> = Snapshot<D>> {
+    holder: Holder<D>,
+    undo: VecDeque<R>,
+    redo: Vec<R>,
+    limit: usize,
+    // the modification count of data when the history last saw it
+    modification: usize,
+}
+
+pub struct HistoryOwner<'a, D: ?Sized, R: Revision<D>> {
+    history: &'a mut History<D, R>,
+    owner: Option<Owner<D>>,
+    base: Option<R::Base>,
+}
+
+impl<D: Clone> Revision<D> for Snapshot<D> {
+    type Base = D;
+
+    fn base(data: &D) -> Self::Base {
+        data.clone()
+    }
+
+    fn diff(base: Self::Base, _data: &D) -> Option<Self> {
+        Some(Snapshot { data: base })
+    }
+
+    fn revert(self, data: &mut D) -> Self {
+        Snapshot { data: mem::replace(data, self.data) }
+    }
+}
+
+impl<D: ?Sized, R: Revision<D>> History<D, R> {
+    // keep at most limit revisions to undo
+    pub fn new(holder: Holder<D>, limit: usize) -> Self {
+        let modification = Holder::modification_count(&holder);
+        Self { holder, undo: VecDeque::new(), redo: Vec::new(), limit, modification }
+    }
+
+    pub fn holder(&self) -> &Holder<D> {
+        &self.holder
+    }
+
+    pub fn state(&self) -> State {
+        Holder::state(&self.holder)
+    }
+
+    pub fn undo_count(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_count(&self) -> usize {
+        self.redo.len()
+    }
+
+    // edits are recorded when the returned owner is dropped, and clear the redo revisions
+    // the edit isn't recorded when data isn't mutably accessed
+    pub fn edit(&mut self) -> Result<HistoryOwner<'_, D, R>, State> {
+        self.sync();
+        let owner = Owner::try_from(&self.holder)?;
+        let base = R::base(&owner);
+        Ok(HistoryOwner { history: self, owner: Some(owner), base: Some(base) })
+    }
+
+    // return false when there is nothing to undo
+    pub fn undo(&mut self) -> Result<bool, State> {
+        self.sync();
+        if self.undo.is_empty() {
+            return Ok(false);
+        }
+        let mut owner = Owner::try_from(&self.holder)?;
+        let revision = self.undo.pop_back().unwrap();
+        self.redo.push(revision.revert(&mut owner));
+        self.modification = Holder::modification_count(&self.holder);
+        Ok(true)
+    }
+
+    // return false when there is nothing to redo
+    pub fn redo(&mut self) -> Result<bool, State> {
+        self.sync();
+        if self.redo.is_empty() {
+            return Ok(false);
+        }
+        let mut owner = Owner::try_from(&self.holder)?;
+        let revision = self.redo.pop().unwrap();
+        self.undo.push_back(revision.revert(&mut owner));
+        self.modification = Holder::modification_count(&self.holder);
+        Ok(true)
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    // clear the revisions when data has been modified by other owners
+    fn sync(&mut self) {
+        let modification = Holder::modification_count(&self.holder);
+        if modification != self.modification {
+            self.clear();
+            self.modification = modification;
+        }
+    }
+
+    fn record(&mut self, revision: R) {
+        self.redo.clear();
+        self.undo.push_back(revision);
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+}
+
+impl<D: ?Sized, R: Revision<D>> Debug for History<D, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("History")
+            .field("holder", &self.holder)
+            .field("undo", &self.undo.len())
+            .field("redo", &self.redo.len())
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+impl<D: ?Sized, R: Revision<D>> Deref for HistoryOwner<'_, D, R> {
+    type Target = D;
+    fn deref(&self) -> &Self::Target {
+        self.owner.as_ref().unwrap()
+    }
+}
+
+impl<D: ?Sized, R: Revision<D>> DerefMut for HistoryOwner<'_, D, R> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.owner.as_mut().unwrap()
+    }
+}
+
+impl<D: ?Sized, R: Revision<D>> Drop for HistoryOwner<'_, D, R> {
+    fn drop(&mut self) {
+        let owner = self.owner.take().unwrap();
+        let base = self.base.take().unwrap();
+        let modification = Holder::modification_count(&self.history.holder);
+        // record before releasing, so subscribers see the new revision
+        if modification != self.history.modification
+            && let Some(revision) = R::diff(base, &owner)
+        {
+            self.history.record(revision);
+        }
+        self.history.modification = modification;
+        drop(owner);
+    }
+}
+
+impl<D: ?Sized, R: Revision<D>> Debug for HistoryOwner<'_, D, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HistoryOwner").field("owner", &self.owner).finish()
+    }
+}
@@ -1,11 +1,26 @@
-use std::fmt::Debug;
-use std::fmt::Formatter;
-use std::hash::Hash;
-use std::hash::Hasher;
+#[cfg(feature = "subscribe")]
+use alloc::rc::Rc;
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::mem;
+use core::mem::ManuallyDrop;
 
+use allocator_api2::alloc::Allocator;
+
+use crate::Access;
+#[cfg(feature = "subscribe")]
+use crate::Change;
+use crate::CowViewer;
+use crate::Frozen;
 use crate::Owner;
 use crate::OwnerRef;
 use crate::State;
+#[cfg(feature = "subscribe")]
+use crate::Subscription;
+use crate::UniqueOwner;
 use crate::Viewer;
 use crate::ViewerRef;
 use crate::ptr::Ptr;
@@ -20,6 +35,17 @@ impl<D: ?Sized> Holder<D> {
         Self { ptr: Ptr::new_holder(data) }
     }
 
+    pub fn new_in<A: Allocator + Send + Sync + 'static>(data: D, alloc: A) -> Self
+    where D: Sized {
+        Self { ptr: Ptr::new_holder_in(data, alloc, false) }
+    }
+
+    // the allocation can't be sent to another thread
+    pub(crate) fn new_local_in<A: Allocator + 'static>(data: D, alloc: A) -> Self
+    where D: Sized {
+        Self { ptr: Ptr::new_holder_in(data, alloc, true) }
+    }
+
     pub fn state(holder: &Self) -> State {
         holder.ptr.cell().state()
     }
@@ -27,7 +53,7 @@ impl<D: ?Sized> Holder<D> {
     pub fn reinit(holder: &Self, data: D) -> Result<(), State>
     where D: Sized {
         let state = holder.ptr.cell().state();
-        if state.is_dropped() {
+        if state.is_dropped() && !state.is_frozen() {
             // SAFETY: data is dropped
             unsafe {
                 holder.ptr.cell().reinit_data(data);
@@ -38,6 +64,106 @@ impl<D: ?Sized> Holder<D> {
         }
     }
 
+    // clone the data into a new allocation, which fails when data is owned or dropped
+    pub fn try_clone_data(holder: &Self) -> Result<Holder<D>, State>
+    where D: Clone {
+        let viewer = Viewer::try_from(holder)?;
+        Ok(Holder::new(D::clone(&viewer)))
+    }
+
+    // succeed when it is the last handle and data hasn't been dropped
+    pub fn try_into_inner(holder: Self) -> Result<D, Self>
+    where D: Sized {
+        let state = holder.ptr.cell().state();
+        if state.is_dropped()
+            || state.holder_count() != 1
+            || state.viewer_count() != 0
+            || state.is_owned()
+        {
+            return Err(holder);
+        }
+        // SAFETY:
+        // we are the last handle
+        // we consume the Holder when taking
+        // we change the state to dropped
+        // so we won't access the data anymore
+        Ok(unsafe { holder.ptr.cell().move_data() })
+    }
+
+    // data may be dropped, so its alignment is only known when it is sized
+    pub fn into_raw(holder: Self) -> *const D
+    where D: Sized {
+        let data = holder.ptr.cell().data_ptr();
+        mem::forget(holder);
+        data
+    }
+
+    /// # Safety
+    ///
+    /// `data` must be returned by `Holder::into_raw`, and one holder count of the `StateCell`
+    /// is taken over by the returned `Holder`, so it must be called only once for each count.
+    pub unsafe fn from_raw(data: *const D) -> Self
+    where D: Sized {
+        // SAFETY: data is the data ptr of a StateCell
+        Self { ptr: unsafe { Ptr::from_data_ptr(data, align_of::<D>()) } }
+    }
+
+    pub fn as_ptr(holder: &Self) -> *const D {
+        holder.ptr.cell().data_ptr()
+    }
+
+    /// # Safety
+    ///
+    /// `data` must be returned by `Holder::into_raw`, and there must be a holder count of the `StateCell`
+    /// which is not released during this call. The new holder count should be released by `Holder::from_raw`
+    /// or `Holder::decrement_count`.
+    pub unsafe fn increment_count(data: *const D)
+    where D: Sized {
+        // SAFETY: the safety requirements are the same
+        let holder = ManuallyDrop::new(unsafe { Self::from_raw(data) });
+        mem::forget(Self::clone(&holder));
+    }
+
+    /// # Safety
+    ///
+    /// `data` must be returned by `Holder::into_raw`, and one holder count of the `StateCell` is released,
+    /// so it must be called only once for each count.
+    pub unsafe fn decrement_count(data: *const D)
+    where D: Sized {
+        // SAFETY: the safety requirements are the same
+        drop(unsafe { Self::from_raw(data) });
+    }
+
+    #[cfg(feature = "revocable")]
+    pub fn revoke_all(holder: &Self) -> Result<(), State> {
+        holder.ptr.cell().revoke_all()
+    }
+
+    // whether owners have mutably accessed data since the dirty flag is cleared
+    #[cfg(feature = "dirty")]
+    pub fn is_dirty(holder: &Self) -> bool {
+        holder.ptr.cell().is_dirty()
+    }
+
+    // the count of mutable access by owners, which wraps around
+    #[cfg(feature = "dirty")]
+    pub fn modification_count(holder: &Self) -> usize {
+        holder.ptr.cell().modification_count()
+    }
+
+    #[cfg(feature = "dirty")]
+    pub fn clear_dirty(holder: &Self) {
+        holder.ptr.cell().clear_dirty();
+    }
+
+    // the callback is called after the last owner releases, reinit and drop of data
+    // an owner converted into another owner doesn't release data
+    #[cfg(feature = "subscribe")]
+    pub fn subscribe<F: Fn(Change) + 'static>(holder: &Self, callback: F) -> Subscription<D> {
+        let id = holder.ptr.cell().subscribe(Rc::new(callback));
+        Subscription::new(Self::clone(holder), id)
+    }
+
     pub(crate) fn ptr(holder: &Self) -> &Ptr<D> {
         &holder.ptr
     }
@@ -103,8 +229,38 @@ impl<Source: ?Sized, Target: ?Sized> From<OwnerRef<Source, Target>> for Holder<S
     }
 }
 
+impl<D: ?Sized> From<&Frozen<D>> for Holder<D> {
+    fn from(value: &Frozen<D>) -> Self {
+        Self { ptr: Frozen::ptr(value).clone_to_holder() }
+    }
+}
+
+impl<D: ?Sized> From<Frozen<D>> for Holder<D> {
+    fn from(value: Frozen<D>) -> Self {
+        Self { ptr: Frozen::ptr(&value).clone_to_holder() }
+    }
+}
+
+impl<D: ?Sized> From<&CowViewer<D>> for Holder<D> {
+    fn from(value: &CowViewer<D>) -> Self {
+        Self { ptr: CowViewer::ptr(value).clone_to_holder() }
+    }
+}
+
+impl<D: ?Sized> From<&Access<D>> for Holder<D> {
+    fn from(value: &Access<D>) -> Self {
+        Self { ptr: value.ptr().clone_to_holder() }
+    }
+}
+
+impl<D: ?Sized> From<UniqueOwner<D>> for Holder<D> {
+    fn from(value: UniqueOwner<D>) -> Self {
+        Self { ptr: UniqueOwner::ptr(&value).clone_to_holder() }
+    }
+}
+
 impl<D: ?Sized> Debug for Holder<D> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_tuple("Holder").field(&self.ptr).finish()
     }
 }
@@ -128,3 +284,15 @@ impl<D: ?Sized> Hash for Holder<D> {
         self.ptr.hash(state);
     }
 }
+
+impl<D: ?Sized> PartialOrd for Holder<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<D: ?Sized> Ord for Holder<D> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ptr.cmp(&other.ptr)
+    }
+}
@@ -1,7 +1,52 @@
+#![no_std]
+
+extern crate alloc;
+
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+pub use crate::access::Access;
+pub use crate::access::AccessLevel;
+pub use crate::bundle::BundleHandle;
+pub use crate::bundle::HandleBundle;
+pub use crate::bundle::SendHandleBundle;
+pub use crate::cow_viewer::CowViewer;
+pub use crate::forward::ByValue;
+pub use crate::frozen::Frozen;
+pub use crate::handle::AllocId;
+pub use crate::handle::Handle;
+pub use crate::handle::ReadHandle;
+pub use crate::handle::WriteHandle;
+#[cfg(feature = "dirty")]
+pub use crate::history::History;
+#[cfg(feature = "dirty")]
+pub use crate::history::HistoryOwner;
+#[cfg(feature = "dirty")]
+pub use crate::history::Revision;
+#[cfg(feature = "dirty")]
+pub use crate::history::Snapshot;
 pub use crate::holder::Holder;
+pub use crate::own_cell::CellHolder;
+pub use crate::own_cell::CellOwner;
+pub use crate::own_cell::CellViewer;
+pub use crate::own_cell::OwnCell;
 pub use crate::owner::Owner;
 pub use crate::owner_ref::OwnerRef;
+pub use crate::pool::Pool;
 pub use crate::ptr::State;
+#[cfg(feature = "revocable")]
+pub use crate::revocable::Revocable;
+#[cfg(feature = "revocable")]
+pub use crate::revocable::RevocableMut;
+#[cfg(feature = "revocable")]
+pub use crate::revocable::RevocableRef;
+#[cfg(feature = "subscribe")]
+pub use crate::subscription::Change;
+#[cfg(feature = "subscribe")]
+pub use crate::subscription::Subscription;
+pub use crate::transaction::Transaction;
+pub use crate::transaction::Undo;
+pub use crate::unique_owner::UniqueOwner;
 pub use crate::viewer::Viewer;
 pub use crate::viewer_ref::ViewerRef;
 
@@ -9,14 +54,46 @@ mod owner_ref;
 
 mod owner;
 
+mod unique_owner;
+
 mod viewer_ref;
 
 mod viewer;
 
+mod cow_viewer;
+
 mod holder;
 
+mod frozen;
+
+#[cfg(feature = "revocable")]
+mod revocable;
+
 mod ref_;
 
+mod access;
+
+mod handle;
+
+mod forward;
+
+mod transaction;
+
+#[cfg(feature = "dirty")]
+mod history;
+
+#[cfg(feature = "subscribe")]
+mod subscription;
+
+mod bundle;
+
+mod pool;
+
+mod own_cell;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
 mod ptr;
 
 #[cfg(test)]
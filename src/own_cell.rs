@@ -0,0 +1,269 @@
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::mem::ManuallyDrop;
+use core::ops::Deref;
+use core::ops::DerefMut;
+
+use crate::State;
+use crate::ptr::StateCell;
+
+// runtime ownership for data in place, handles borrow the cell so they can't outlive or move it
+pub struct OwnCell<D> {
+    cell: ManuallyDrop<StateCell<D>>,
+}
+
+// SAFETY:
+// handles borrow the cell, so no handle can outlive the move and access the state from another thread
+// the extra storage is only made by revocable handles and subscriptions, which cells in place don't have
+// so moving the cell only moves the data, which is Send
+unsafe impl<D: Send> Send for OwnCell<D> {}
+
+pub struct CellOwner<'a, D> {
+    cell: &'a StateCell<D>,
+}
+
+pub struct CellViewer<'a, D> {
+    cell: &'a StateCell<D>,
+}
+
+pub struct CellHolder<'a, D> {
+    cell: &'a StateCell<D>,
+}
+
+impl<D> OwnCell<D> {
+    pub fn new(data: D) -> Self {
+        Self { cell: ManuallyDrop::new(StateCell::new_in_place(data)) }
+    }
+
+    pub fn state(&self) -> State {
+        self.cell.state()
+    }
+
+    pub fn owner(&self) -> Result<CellOwner<'_, D>, State> {
+        self.cell.clone_to_owner()?;
+        Ok(CellOwner { cell: &self.cell })
+    }
+
+    pub fn viewer(&self) -> Result<CellViewer<'_, D>, State> {
+        self.cell.clone_to_viewer()?;
+        Ok(CellViewer { cell: &self.cell })
+    }
+
+    pub fn holder(&self) -> CellHolder<'_, D> {
+        self.cell.clone_to_holder();
+        CellHolder { cell: &self.cell }
+    }
+
+    pub fn get_mut(&mut self) -> Option<&mut D> {
+        let state = self.cell.state();
+        if state.is_dropped() || state.is_owned() || state.viewer_count() != 0 {
+            return None;
+        }
+        // SAFETY: we have exclusive ref of the cell and no handle can access the data
+        Some(unsafe { StateCell::deref_mut(&self.cell) })
+    }
+
+    pub fn into_inner(mut self) -> Option<D> {
+        self.get_mut()?;
+        // SAFETY: data hasn't been dropped and no handle can access the data
+        Some(unsafe { self.cell.move_data() })
+    }
+}
+
+impl<D> Drop for OwnCell<D> {
+    fn drop(&mut self) {
+        let state = self.cell.state();
+        debug_assert!(
+            state.holder_count() == 1 && state.viewer_count() == 0 && !state.is_owned(),
+            "handles of OwnCell should be dropped before the cell"
+        );
+        if !state.is_dropped() {
+            // SAFETY: no handle can access the data after the cell is dropped
+            unsafe { self.cell.drop_data() }
+        }
+    }
+}
+
+impl<D> CellOwner<'_, D> {
+    pub fn state(owner: &Self) -> State {
+        owner.cell.state()
+    }
+
+    pub fn move_data(owner: Self) -> D {
+        // SAFETY:
+        // we have exclusive ref
+        // we consume the Owner when taking
+        // we change the state to dropped
+        // so we won't access the data anymore
+        unsafe { owner.cell.move_data() }
+    }
+
+    pub fn drop_data(owner: Self) {
+        // SAFETY:
+        // we have exclusive ref
+        // we consume the Owner when deleting
+        // we change the state to dropped
+        // so we won't access the data anymore
+        unsafe { owner.cell.drop_data() }
+    }
+}
+
+impl<D> CellViewer<'_, D> {
+    pub fn state(viewer: &Self) -> State {
+        viewer.cell.state()
+    }
+}
+
+impl<D> CellHolder<'_, D> {
+    pub fn state(holder: &Self) -> State {
+        holder.cell.state()
+    }
+
+    pub fn reinit(holder: &Self, data: D) -> Result<(), State> {
+        let state = holder.cell.state();
+        if state.is_dropped() {
+            // SAFETY: data is dropped
+            unsafe {
+                holder.cell.reinit_data(data);
+            }
+            Ok(())
+        } else {
+            Err(state)
+        }
+    }
+}
+
+impl<D> Deref for CellOwner<'_, D> {
+    type Target = D;
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: we have exclusive ref and data hasn't been dropped
+        unsafe { self.cell.deref() }
+    }
+}
+
+impl<D> DerefMut for CellOwner<'_, D> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.cell.modify();
+        // SAFETY: we have exclusive ref and data hasn't been dropped
+        unsafe { self.cell.deref_mut() }
+    }
+}
+
+impl<D> Deref for CellViewer<'_, D> {
+    type Target = D;
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: when self is alive there is no owner and data hasn't been dropped
+        unsafe { self.cell.deref() }
+    }
+}
+
+impl<D> Clone for CellViewer<'_, D> {
+    fn clone(&self) -> Self {
+        self.cell.clone_to_viewer().unwrap();
+        Self { cell: self.cell }
+    }
+}
+
+impl<D> Clone for CellHolder<'_, D> {
+    fn clone(&self) -> Self {
+        self.cell.clone_to_holder();
+        Self { cell: self.cell }
+    }
+}
+
+impl<D> Drop for CellOwner<'_, D> {
+    fn drop(&mut self) {
+        self.cell.drop_from_owner();
+    }
+}
+
+impl<D> Drop for CellViewer<'_, D> {
+    fn drop(&mut self) {
+        self.cell.drop_from_viewer();
+    }
+}
+
+impl<D> Drop for CellHolder<'_, D> {
+    fn drop(&mut self) {
+        self.cell.drop_from_holder();
+    }
+}
+
+impl<'a, D> TryFrom<&CellHolder<'a, D>> for CellOwner<'a, D> {
+    type Error = State;
+    fn try_from(value: &CellHolder<'a, D>) -> Result<Self, Self::Error> {
+        value.cell.clone_to_owner()?;
+        Ok(Self { cell: value.cell })
+    }
+}
+
+impl<'a, D> TryFrom<CellViewer<'a, D>> for CellOwner<'a, D> {
+    type Error = State;
+    fn try_from(value: CellViewer<'a, D>) -> Result<Self, Self::Error> {
+        let cell = value.cell;
+        drop(value);
+        cell.clone_to_owner()?;
+        Ok(Self { cell })
+    }
+}
+
+impl<'a, D> TryFrom<&CellHolder<'a, D>> for CellViewer<'a, D> {
+    type Error = State;
+    fn try_from(value: &CellHolder<'a, D>) -> Result<Self, Self::Error> {
+        value.cell.clone_to_viewer()?;
+        Ok(Self { cell: value.cell })
+    }
+}
+
+impl<'a, D> From<CellOwner<'a, D>> for CellViewer<'a, D> {
+    fn from(value: CellOwner<'a, D>) -> Self {
+        let cell = value.cell;
+        drop(value);
+        cell.clone_to_viewer().unwrap();
+        Self { cell }
+    }
+}
+
+impl<'a, D> From<&CellOwner<'a, D>> for CellHolder<'a, D> {
+    fn from(value: &CellOwner<'a, D>) -> Self {
+        value.cell.clone_to_holder();
+        Self { cell: value.cell }
+    }
+}
+
+impl<'a, D> From<&CellViewer<'a, D>> for CellHolder<'a, D> {
+    fn from(value: &CellViewer<'a, D>) -> Self {
+        value.cell.clone_to_holder();
+        Self { cell: value.cell }
+    }
+}
+
+impl<D> Debug for OwnCell<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("OwnCell").field(&self.state()).finish()
+    }
+}
+
+impl<D> Debug for CellOwner<'_, D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("CellOwner").field(&self.cell.data_ptr()).finish()
+    }
+}
+
+impl<D> Debug for CellViewer<'_, D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("CellViewer").field(&self.cell.data_ptr()).finish()
+    }
+}
+
+impl<D> Debug for CellHolder<'_, D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("CellHolder").field(&self.cell.data_ptr()).finish()
+    }
+}
+
+impl<D: Default> Default for OwnCell<D> {
+    fn default() -> Self {
+        Self::new(D::default())
+    }
+}
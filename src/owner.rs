@@ -1,16 +1,29 @@
-use std::fmt::Debug;
-use std::fmt::Formatter;
-use std::hash::Hash;
-use std::hash::Hasher;
-use std::ops::Deref;
-use std::ops::DerefMut;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::mem;
+use core::ops::Deref;
+use core::ops::DerefMut;
+use core::ptr;
 
+use allocator_api2::alloc::Allocator;
+
+use crate::Frozen;
 use crate::Holder;
 use crate::OwnerRef;
 use crate::State;
+use crate::Transaction;
+use crate::Undo;
+use crate::UniqueOwner;
 use crate::Viewer;
 use crate::ViewerRef;
 use crate::ptr::Ptr;
+use crate::transaction::Restore;
 
 pub struct Owner<D: ?Sized> {
     ptr: Ptr<D>,
@@ -22,6 +35,43 @@ impl<D: ?Sized> Owner<D> {
         Self { ptr: Ptr::new_owner(data) }
     }
 
+    /// The last handle may deallocate in another thread, so the allocator must be `Send` and `Sync`.
+    ///
+    /// ```compile_fail
+    /// use std::alloc::Layout;
+    /// use std::ptr::NonNull;
+    /// use std::rc::Rc;
+    ///
+    /// use allocator_api2::alloc::AllocError;
+    /// use allocator_api2::alloc::Allocator;
+    /// use allocator_api2::alloc::Global;
+    /// use rt_own::Owner;
+    ///
+    /// struct LocalAlloc(Rc<()>);
+    ///
+    /// unsafe impl Allocator for LocalAlloc {
+    ///     fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+    ///         Global.allocate(layout)
+    ///     }
+    ///
+    ///     unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+    ///         unsafe { Global.deallocate(ptr, layout) }
+    ///     }
+    /// }
+    ///
+    /// Owner::new_in(1, LocalAlloc(Rc::new(())));
+    /// ```
+    pub fn new_in<A: Allocator + Send + Sync + 'static>(data: D, alloc: A) -> Self
+    where D: Sized {
+        Self { ptr: Ptr::new_owner_in(data, alloc, false) }
+    }
+
+    // the allocation can't be sent to another thread
+    pub(crate) fn new_local_in<A: Allocator + 'static>(data: D, alloc: A) -> Self
+    where D: Sized {
+        Self { ptr: Ptr::new_owner_in(data, alloc, true) }
+    }
+
     pub fn state(owner: &Self) -> State {
         owner.ptr.cell().state()
     }
@@ -45,6 +95,66 @@ impl<D: ?Sized> Owner<D> {
         unsafe { owner.ptr.cell().drop_data() }
     }
 
+    // restore data from a snapshot when f fails or panics
+    pub fn transaction<R, E, F>(owner: &mut Self, f: F) -> Result<R, E>
+    where
+        D: Clone,
+        F: FnOnce(&mut D) -> Result<R, E>, {
+        Restore::run(&mut **owner, f)
+    }
+
+    // restore data by the undo log when f fails or panics
+    pub fn transaction_with_log<R, E, U, F>(owner: &mut Self, f: F) -> Result<R, E>
+    where
+        U: Undo<D>,
+        F: FnOnce(&mut Transaction<'_, D, U>) -> Result<R, E>, {
+        Transaction::run(&mut **owner, f)
+    }
+
+    pub fn into_box(owner: Self) -> Box<D>
+    where D: Sized {
+        Box::new(Self::move_data(owner))
+    }
+
+    // the data stays owned forever, so holders can't view, own or reinit it
+    pub fn leak(owner: Self) -> &'static mut D
+    where D: 'static {
+        owner.ptr.cell().modify();
+        // SAFETY: we never release the owned state, so the data is exclusively ours forever
+        let data = unsafe { owner.ptr.cell().deref_mut() };
+        mem::forget(owner);
+        data
+    }
+
+    pub fn into_raw(owner: Self) -> *mut D {
+        let data = owner.ptr.cell().data_ptr();
+        mem::forget(owner);
+        data
+    }
+
+    /// # Safety
+    ///
+    /// `data` must be returned by `Owner::into_raw`, and the owned state of the `StateCell`
+    /// is taken over by the returned `Owner`, so it must be called only once for each `into_raw`.
+    pub unsafe fn from_raw(data: *mut D) -> Self {
+        // SAFETY: data is owned by us and hasn't been dropped
+        let align = align_of_val(unsafe { &*data });
+        // SAFETY: data is the data ptr of a StateCell
+        Self { ptr: unsafe { Ptr::from_data_ptr(data, align) } }
+    }
+
+    pub fn as_ptr(owner: &Self) -> *const D {
+        owner.ptr.cell().data_ptr()
+    }
+
+    pub fn freeze(owner: Self) -> Frozen<D> {
+        Frozen::from(owner)
+    }
+
+    pub fn try_into_unique(owner: Self) -> Result<UniqueOwner<D>, Self> {
+        UniqueOwner::try_from(owner)
+    }
+
     pub(crate) fn ptr(owner: &Self) -> &Ptr<D> {
         &owner.ptr
     }
@@ -60,6 +170,7 @@ impl<D: ?Sized> Deref for Owner<D> {
 
 impl<D: ?Sized> DerefMut for Owner<D> {
     fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ptr.cell().modify();
         // SAFETY: we have exclusive ref and data hasn't been dropped
         unsafe { self.ptr.cell().deref_mut() }
     }
@@ -103,13 +214,95 @@ impl<Source: ?Sized, Target: ?Sized> TryFrom<ViewerRef<Source, Target>> for Owne
 
 impl<Source: ?Sized, Target: ?Sized> From<OwnerRef<Source, Target>> for Owner<Source> {
     fn from(value: OwnerRef<Source, Target>) -> Self {
-        let holder = Holder::from(value);
-        Self { ptr: Holder::ptr(&holder).clone_to_owner().unwrap() }
+        let ptr = OwnerRef::source(&value).transfer();
+        mem::forget(value);
+        Self { ptr }
+    }
+}
+
+impl<D: ?Sized> From<UniqueOwner<D>> for Owner<D> {
+    fn from(value: UniqueOwner<D>) -> Self {
+        let ptr = UniqueOwner::ptr(&value).transfer();
+        mem::forget(value);
+        Self { ptr }
+    }
+}
+
+impl<D> From<Box<D>> for Owner<D> {
+    fn from(value: Box<D>) -> Self {
+        Self::new(*value)
+    }
+}
+
+impl<T> From<Box<[T]>> for Owner<[T]> {
+    fn from(value: Box<[T]>) -> Self {
+        let ptr = Ptr::new_owner_from_box(value, |data, addr| {
+            ptr::slice_from_raw_parts_mut(addr.cast(), data.len())
+        });
+        Self { ptr }
+    }
+}
+
+impl From<Box<str>> for Owner<str> {
+    fn from(value: Box<str>) -> Self {
+        let ptr = Ptr::new_owner_from_box(value, |data, addr| {
+            ptr::slice_from_raw_parts_mut(addr, (data as *mut [u8]).len()) as *mut str
+        });
+        Self { ptr }
+    }
+}
+
+impl<T> From<Vec<T>> for Owner<[T]> {
+    fn from(value: Vec<T>) -> Self {
+        Self::from(value.into_boxed_slice())
+    }
+}
+
+impl<D> TryFrom<Rc<D>> for Owner<D> {
+    type Error = Rc<D>;
+    fn try_from(value: Rc<D>) -> Result<Self, Self::Error> {
+        Rc::try_unwrap(value).map(Self::new)
+    }
+}
+
+impl<T> From<Owner<[T]>> for Box<[T]> {
+    fn from(value: Owner<[T]>) -> Self {
+        // SAFETY:
+        // we have exclusive ref
+        // we consume the Owner when taking
+        // we change the state to dropped
+        // so we won't access the data anymore
+        unsafe {
+            value.ptr.cell().move_data_to_box(|data, addr| {
+                ptr::slice_from_raw_parts_mut(addr.cast(), data.len())
+            })
+        }
+    }
+}
+
+impl From<Owner<str>> for Box<str> {
+    fn from(value: Owner<str>) -> Self {
+        // SAFETY:
+        // we have exclusive ref
+        // we consume the Owner when taking
+        // we change the state to dropped
+        // so we won't access the data anymore
+        unsafe {
+            value.ptr.cell().move_data_to_box(|data, addr| {
+                ptr::slice_from_raw_parts_mut(addr, (data as *mut [u8]).len()) as *mut str
+            })
+        }
+    }
+}
+
+impl<T> From<Owner<[T]>> for Vec<T> {
+    fn from(value: Owner<[T]>) -> Self {
+        Box::<[T]>::from(value).into_vec()
     }
 }
 
 impl<D: ?Sized> Debug for Owner<D> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_tuple("Owner").field(&self.ptr).finish()
     }
 }
@@ -133,3 +326,15 @@ impl<D: ?Sized> Hash for Owner<D> {
         self.ptr.hash(state);
     }
 }
+
+impl<D: ?Sized> PartialOrd for Owner<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<D: ?Sized> Ord for Owner<D> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ptr.cmp(&other.ptr)
+    }
+}
@@ -1,17 +1,22 @@
-use std::fmt::Debug;
-use std::fmt::Formatter;
-use std::hash::Hash;
-use std::hash::Hasher;
-use std::ops::Deref;
-use std::ops::DerefMut;
-use std::ptr::NonNull;
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::mem;
+use core::ops::Deref;
+use core::ops::DerefMut;
+use core::ptr::NonNull;
 
 use crate::Holder;
 use crate::Owner;
 use crate::State;
+use crate::Transaction;
+use crate::Undo;
 use crate::Viewer;
 use crate::ptr::Ptr;
 use crate::ref_::Ref;
+use crate::transaction::Restore;
 
 pub struct OwnerRef<Source: ?Sized, Target: ?Sized> {
     ref_: Ref<Source, Target>,
@@ -26,10 +31,11 @@ impl<Source: ?Sized, Target: ?Sized> OwnerRef<Source, Target> {
     where
         Target2: ?Sized + 'static,
         Map: for<'a> FnOnce(&'a mut Target) -> &'a mut Target2, {
+        owner.ref_.source().cell().modify();
         // SAFETY: when self is alive there is no owner and data hasn't been dropped
         let target = unsafe { owner.ref_.map_target_mut(map) };
-        let holder = Holder::from(owner);
-        let source = Holder::ptr(&holder).clone_to_owner().unwrap();
+        let source = owner.ref_.source().transfer();
+        mem::forget(owner);
         OwnerRef { ref_: Ref::new(source, target) }
     }
 
@@ -39,13 +45,30 @@ impl<Source: ?Sized, Target: ?Sized> OwnerRef<Source, Target> {
     where
         Target2: ?Sized + 'static,
         Map: for<'a> FnOnce(&'a mut Target) -> Result<&'a mut Target2, Err>, {
+        owner.ref_.source().cell().modify();
         // SAFETY: when self is alive there is no owner and data hasn't been dropped
         let target = unsafe { owner.ref_.try_map_target_mut(map) }?;
-        let holder = Holder::from(owner);
-        let source = Holder::ptr(&holder).clone_to_owner().unwrap();
+        let source = owner.ref_.source().transfer();
+        mem::forget(owner);
         Ok(OwnerRef { ref_: Ref::new(source, target) })
     }
 
+    // restore the target from a snapshot when f fails or panics
+    pub fn transaction<R, E, F>(owner: &mut Self, f: F) -> Result<R, E>
+    where
+        Target: Clone,
+        F: FnOnce(&mut Target) -> Result<R, E>, {
+        Restore::run(&mut **owner, f)
+    }
+
+    // restore the target by the undo log when f fails or panics
+    pub fn transaction_with_log<R, E, U, F>(owner: &mut Self, f: F) -> Result<R, E>
+    where
+        U: Undo<Target>,
+        F: FnOnce(&mut Transaction<'_, Target, U>) -> Result<R, E>, {
+        Transaction::run(&mut **owner, f)
+    }
+
     pub(crate) fn source(owner: &Self) -> &Ptr<Source> {
         owner.ref_.source()
     }
@@ -71,6 +94,7 @@ impl<Source: ?Sized, Target: ?Sized> Deref for OwnerRef<Source, Target> {
 
 impl<Source: ?Sized, Target: ?Sized> DerefMut for OwnerRef<Source, Target> {
     fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ref_.source().cell().modify();
         // SAFETY: we have exclusive ref and data hasn't been dropped
         unsafe { self.ref_.deref_mut() }
     }
@@ -103,14 +127,14 @@ impl<Source: ?Sized> TryFrom<Viewer<Source>> for OwnerRef<Source, Source> {
 
 impl<Source: ?Sized> From<Owner<Source>> for OwnerRef<Source, Source> {
     fn from(value: Owner<Source>) -> Self {
-        let holder = Holder::from(value);
-        let source = Holder::ptr(&holder).clone_to_owner().unwrap();
+        let source = Owner::ptr(&value).transfer();
+        mem::forget(value);
         Self { ref_: Ref::from_source(source) }
     }
 }
 
 impl<Source: ?Sized, Target: ?Sized> Debug for OwnerRef<Source, Target> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.write_str("Owner")?;
         self.ref_.fmt(f)
     }
@@ -129,3 +153,15 @@ impl<Source: ?Sized, Target: ?Sized> Hash for OwnerRef<Source, Target> {
         self.ref_.hash(state);
     }
 }
+
+impl<Source: ?Sized, Target: ?Sized> PartialOrd for OwnerRef<Source, Target> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized> Ord for OwnerRef<Source, Target> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ref_.cmp(&other.ref_)
+    }
+}
@@ -0,0 +1,173 @@
+use alloc::alloc::alloc;
+use alloc::alloc::dealloc;
+use alloc::alloc::handle_alloc_error;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::cell::Cell;
+use core::cell::RefCell;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+
+use allocator_api2::alloc::AllocError;
+use allocator_api2::alloc::Allocator;
+
+use crate::Holder;
+use crate::Owner;
+use crate::Viewer;
+use crate::ptr::StateCell;
+use crate::ptr::layout_in;
+
+// allocate StateCells of the same type in chunks and reuse them after dealloc
+// the pool isn't thread-safe, so its handles can't be sent to another thread
+pub struct Pool<D> {
+    alloc: PoolAlloc,
+    phantom: PhantomData<fn(D) -> D>,
+}
+
+#[derive(Clone)]
+struct PoolAlloc {
+    inner: Rc<PoolInner>,
+}
+
+struct PoolInner {
+    slot: Layout,
+    chunk_len: usize,
+    chunks: RefCell<Vec<NonNull<u8>>>,
+    // cnt of used slots in the last chunk
+    used: Cell<usize>,
+    // every free slot stores the next free slot
+    free: Cell<Option<NonNull<u8>>>,
+}
+
+const DEFAULT_CHUNK_LEN: usize = 64;
+
+impl<D> Pool<D> {
+    pub fn new() -> Self {
+        Self::with_chunk_len(DEFAULT_CHUNK_LEN)
+    }
+
+    pub fn with_chunk_len(chunk_len: usize) -> Self {
+        assert!(chunk_len > 0, "chunk len should be positive");
+        let (slot, _) = layout_in::<PoolAlloc>(Layout::new::<StateCell<D>>());
+        let inner = PoolInner {
+            slot,
+            chunk_len,
+            chunks: RefCell::new(Vec::new()),
+            used: Cell::new(chunk_len),
+            free: Cell::new(None),
+        };
+        Self { alloc: PoolAlloc { inner: Rc::new(inner) }, phantom: PhantomData }
+    }
+
+    pub fn holder(&self, data: D) -> Holder<D> {
+        Holder::new_local_in(data, self.alloc.clone())
+    }
+
+    pub fn viewer(&self, data: D) -> Viewer<D> {
+        Viewer::new_local_in(data, self.alloc.clone())
+    }
+
+    pub fn owner(&self, data: D) -> Owner<D> {
+        Owner::new_local_in(data, self.alloc.clone())
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.alloc.inner.chunks.borrow().len()
+    }
+}
+
+impl PoolInner {
+    fn alloc_slot(&self) -> NonNull<u8> {
+        if let Some(slot) = self.free.get() {
+            // SAFETY: every free slot stores the next free slot
+            let next = unsafe { slot.cast::<Option<NonNull<u8>>>().read() };
+            self.free.set(next);
+            return slot;
+        }
+        let mut chunks = self.chunks.borrow_mut();
+        if self.used.get() == self.chunk_len {
+            let layout = self.chunk_layout();
+            // SAFETY: layout has non-zero size
+            let chunk = unsafe { alloc(layout) };
+            let Some(chunk) = NonNull::new(chunk) else {
+                handle_alloc_error(layout);
+            };
+            chunks.push(chunk);
+            self.used.set(0);
+        }
+        let chunk = *chunks.last().unwrap();
+        // SAFETY: the slot is in the chunk
+        let slot = unsafe { chunk.add(self.used.get() * self.slot.size()) };
+        self.used.set(self.used.get() + 1);
+        slot
+    }
+
+    // SAFETY: slot is allocated by alloc_slot and won't be accessed anymore
+    unsafe fn dealloc_slot(&self, slot: NonNull<u8>) {
+        // SAFETY: the slot is large enough and aligned to store a pointer
+        unsafe {
+            slot.cast::<Option<NonNull<u8>>>().write(self.free.get());
+        }
+        self.free.set(Some(slot));
+    }
+
+    fn chunk_layout(&self) -> Layout {
+        let size = self.slot.size().checked_mul(self.chunk_len).expect("chunk size overflow");
+        Layout::from_size_align(size, self.slot.align()).unwrap()
+    }
+}
+
+impl Drop for PoolInner {
+    fn drop(&mut self) {
+        let layout = self.chunk_layout();
+        for chunk in self.chunks.get_mut().drain(..) {
+            // SAFETY: the chunk is allocated with layout and there is no living slot in it
+            unsafe {
+                dealloc(chunk.as_ptr(), layout);
+            }
+        }
+    }
+}
+
+// SAFETY:
+// slots are valid until the last PoolAlloc is dropped
+// and every StateCell in the pool holds a PoolAlloc
+unsafe impl Allocator for PoolAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout != self.inner.slot {
+            return Err(AllocError);
+        }
+        let slot = self.inner.alloc_slot();
+        Ok(NonNull::slice_from_raw_parts(slot, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+        // SAFETY: ptr is allocated by self
+        unsafe { self.inner.dealloc_slot(ptr) }
+    }
+}
+
+impl<D> Clone for Pool<D> {
+    fn clone(&self) -> Self {
+        Self { alloc: self.alloc.clone(), phantom: PhantomData }
+    }
+}
+
+impl<D> Default for Pool<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D> Debug for Pool<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Pool")
+            .field("slot", &self.alloc.inner.slot)
+            .field("chunk_len", &self.alloc.inner.chunk_len)
+            .field("chunk_count", &self.chunk_count())
+            .finish()
+    }
+}
@@ -1,29 +1,97 @@
-use std::alloc;
-use std::cell::Cell;
-use std::cell::UnsafeCell;
-use std::fmt::Debug;
-use std::fmt::Formatter;
-use std::hash::Hash;
-use std::hash::Hasher;
-use std::marker::PhantomData;
-use std::ptr;
-use std::ptr::NonNull;
+use alloc::alloc::alloc;
+use alloc::alloc::dealloc;
+use alloc::alloc::handle_alloc_error;
+use alloc::boxed::Box;
+#[cfg(feature = "subscribe")]
+use alloc::rc::Rc;
+#[cfg(feature = "subscribe")]
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::cell::Cell;
+#[cfg(feature = "subscribe")]
+use core::cell::RefCell;
+use core::cell::UnsafeCell;
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::marker::PhantomData;
+use core::ptr;
+use core::ptr::NonNull;
+
+use allocator_api2::alloc::Allocator;
+
+#[cfg(feature = "subscribe")]
+use crate::Change;
 
 pub(crate) struct Ptr<D: ?Sized> {
     ptr: NonNull<StateCell<D>>,
     phantom: PhantomData<StateCell<D>>,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone)]
 pub struct State {
     // the sign bit indicates whether data has been dropped (negative)
+    // the next bit indicates whether data has been frozen
+    // the next two bits indicates whether the allocator is custom and local
     // other bits indicates holder cnt
-    holder_cnt: isize,
+    holder_cnt: Cnt,
     // the sign bit indicates whether data has been owned (negative)
     // other bits indicates viewer cnt
-    viewer_cnt: isize,
+    viewer_cnt: Cnt,
 }
 
+#[cfg(not(feature = "compact-state"))]
+type Cnt = isize;
+
+#[cfg(feature = "compact-state")]
+type Cnt = i32;
+
+// allocated on demand, so allocations without revocation or subscription don't pay for it
+#[cfg(any(feature = "revocable", feature = "subscribe"))]
+#[derive(Default)]
+struct Extra {
+    #[cfg(feature = "revocable")]
+    revocation: Cell<Revocation>,
+    #[cfg(feature = "subscribe")]
+    subscribers: RefCell<Vec<(usize, Callback)>>,
+    #[cfg(feature = "subscribe")]
+    next_subscriber: Cell<usize>,
+}
+
+#[cfg(feature = "revocable")]
+#[derive(Copy, Clone, Default)]
+struct Revocation {
+    epoch: usize,
+    // viewer cnt of revocable handles in current epoch
+    viewer_cnt: usize,
+    // whether data has been owned by a revocable handle in current epoch
+    owned: bool,
+    // cnt of refs from revocable handles
+    borrow_cnt: usize,
+}
+
+const FROZEN: Cnt = 1 << (Cnt::BITS - 2);
+
+// whether the cell is allocated by a custom allocator
+const ALLOC: Cnt = FROZEN >> 1;
+
+// whether the custom allocator can only be used in the current thread
+const LOCAL: Cnt = ALLOC >> 1;
+
+const HOLDER_CNT: Cnt = LOCAL - 1;
+
+// whether data has been modified since the dirty flag is cleared
+#[cfg(feature = "dirty")]
+const DIRTY: usize = 1 << (usize::BITS - 1);
+
+// stored right before the StateCell allocated by a custom allocator
+type Dealloc = unsafe fn(NonNull<u8>, Layout);
+
+#[cfg(feature = "subscribe")]
+type Callback = Rc<dyn Fn(Change)>;
+
 impl<D: ?Sized> Ptr<D> {
     pub(crate) fn new_holder(data: D) -> Self
     where D: Sized {
@@ -46,6 +114,113 @@ impl<D: ?Sized> Ptr<D> {
         Ptr { ptr: NonNull::from_mut(ptr), phantom: PhantomData }
     }
 
+    pub(crate) fn new_holder_in<A: Allocator + 'static>(data: D, alloc: A, local: bool) -> Self
+    where D: Sized {
+        Self::new_in(data, State::new_holder().local(local), alloc)
+    }
+
+    pub(crate) fn new_viewer_in<A: Allocator + 'static>(data: D, alloc: A, local: bool) -> Self
+    where D: Sized {
+        Self::new_in(data, State::new_viewer().local(local), alloc)
+    }
+
+    pub(crate) fn new_owner_in<A: Allocator + 'static>(data: D, alloc: A, local: bool) -> Self
+    where D: Sized {
+        Self::new_in(data, State::new_owner().local(local), alloc)
+    }
+
+    // the allocator is stored in the same allocation before the StateCell
+    // and the allocator must be 'static because we don't track its lifetime
+    // the allocation must stay in the current thread when the allocator is local
+    fn new_in<A: Allocator + 'static>(data: D, state: State, alloc: A) -> Self
+    where D: Sized {
+        let (layout, offset) = layout_in::<A>(Layout::new::<StateCell<D>>());
+        let Ok(base) = alloc.allocate(layout) else {
+            handle_alloc_error(layout);
+        };
+        let base = base.cast::<u8>();
+        // SAFETY: the allocator is at the start of the allocation
+        unsafe {
+            base.cast::<A>().write(alloc);
+        }
+        // SAFETY: offset is in the allocation
+        let ptr = unsafe { base.add(offset) };
+        // SAFETY: the dealloc fn is right before the StateCell and after the allocator
+        let dealloc = unsafe { ptr.sub(size_of::<Dealloc>()) };
+        // SAFETY: the StateCell is aligned, so is the dealloc fn
+        unsafe {
+            dealloc.cast::<Dealloc>().write(dealloc_in::<A>);
+        }
+        let ptr = ptr.cast::<StateCell<D>>();
+        // SAFETY: the StateCell is at offset of the allocation
+        unsafe {
+            ptr.write(StateCell::new(data, state.alloc()));
+        }
+        Ptr { ptr, phantom: PhantomData }
+    }
+
+    // copy the data into a new allocation without dropping it
+    // with_addr makes a ptr at the addr with the metadata of the ptr
+    pub(crate) fn new_owner_from_box(
+        data: Box<D>, with_addr: fn(*mut D, *mut u8) -> *mut D,
+    ) -> Self {
+        let data = Box::into_raw(data);
+        // SAFETY: data is made by Box::into_raw
+        let data_layout = Layout::for_value(unsafe { &*data });
+        let (layout, _) = Layout::new::<StateCell<()>>().extend(data_layout).unwrap();
+        let layout = layout.pad_to_align();
+        // SAFETY: layout has non-zero size
+        let base = unsafe { alloc(layout) };
+        if base.is_null() {
+            handle_alloc_error(layout);
+        }
+        let cell = with_addr(data, base) as *mut StateCell<D>;
+        // SAFETY: cell is allocated with the layout of StateCell
+        let state = unsafe { &raw mut (*cell).state };
+        // SAFETY: state is valid for writes
+        unsafe {
+            state.write(Cell::new(State::new_owner()));
+        }
+        #[cfg(any(feature = "revocable", feature = "subscribe"))]
+        {
+            // SAFETY: cell is allocated with the layout of StateCell
+            let extra = unsafe { &raw mut (*cell).extra };
+            // SAFETY: extra is valid for writes
+            unsafe {
+                extra.write(Cell::new(None));
+            }
+        }
+        #[cfg(feature = "dirty")]
+        {
+            // SAFETY: cell is allocated with the layout of StateCell
+            let modification = unsafe { &raw mut (*cell).modification };
+            // SAFETY: modification is valid for writes
+            unsafe {
+                modification.write(Cell::new(0));
+            }
+        }
+        // SAFETY: cell is allocated with the layout of StateCell
+        let cell_data = unsafe { &raw mut (*cell).data };
+        // SAFETY: cell_data is valid for writes of the data and doesn't overlap with data
+        unsafe {
+            ptr::copy_nonoverlapping(data.cast::<u8>(), cell_data.cast::<u8>(), data_layout.size());
+        }
+        if data_layout.size() != 0 {
+            // SAFETY: data is moved and the box is allocated by the global allocator
+            unsafe {
+                dealloc(data.cast(), data_layout);
+            }
+        }
+        // SAFETY: cell is allocated
+        let ptr = unsafe { NonNull::new_unchecked(cell) };
+        Ptr { ptr, phantom: PhantomData }
+    }
+
+    // take over the count of a handle, which must be forgotten without dropping
+    pub(crate) fn transfer(&self) -> Self {
+        Ptr { ptr: self.ptr, phantom: PhantomData }
+    }
+
     pub(crate) fn clone_to_holder(&self) -> Self {
         self.cell().clone_to_holder();
         Ptr { ptr: self.ptr, phantom: PhantomData }
@@ -61,6 +236,21 @@ impl<D: ?Sized> Ptr<D> {
         Ok(Ptr { ptr: self.ptr, phantom: PhantomData })
     }
 
+    pub(crate) fn clone_to_frozen(&self) -> Self {
+        self.cell().clone_to_frozen();
+        Ptr { ptr: self.ptr, phantom: PhantomData }
+    }
+
+    pub(crate) fn freeze(&self) {
+        self.cell().freeze();
+    }
+
+    #[cfg(feature = "revocable")]
+    pub(crate) fn clone_to_revocable(&self, owned: bool) -> Result<Self, State> {
+        self.cell().clone_to_revocable(owned)?;
+        Ok(Ptr { ptr: self.ptr, phantom: PhantomData })
+    }
+
     pub(crate) fn drop_from_holder(&self) {
         self.cell().drop_from_holder();
         self.check_dealloc();
@@ -76,27 +266,92 @@ impl<D: ?Sized> Ptr<D> {
         self.check_dealloc();
     }
 
+    #[cfg(feature = "revocable")]
+    pub(crate) fn drop_from_revocable(&self, epoch: usize, owned: bool) {
+        self.cell().drop_from_revocable(epoch, owned);
+        self.check_dealloc();
+    }
+
     fn check_dealloc(&self) {
         if self.cell().should_dealloc() {
-            let layout = alloc::Layout::for_value(self.cell());
-            // SAFETY:
-            // state promises that we can and should dealloc
-            // we are the last Ptr accessible to the ptr of PtrCell, and we are dropped
-            // we carefully don't make any ref to PtrCell when calling dealloc
-            unsafe {
-                alloc::dealloc(self.ptr.as_ptr().cast(), layout);
+            #[cfg(any(feature = "revocable", feature = "subscribe"))]
+            self.cell().drop_extra();
+            let layout = Layout::for_value(self.cell());
+            if self.cell().state().has_alloc() {
+                let ptr = self.ptr.cast::<u8>();
+                // SAFETY: the dealloc fn is right before the StateCell
+                let dealloc = unsafe { ptr.sub(size_of::<Dealloc>()) };
+                // SAFETY: the dealloc fn is written by Ptr::new_in
+                let dealloc = unsafe { dealloc.cast::<Dealloc>().read() };
+                // SAFETY:
+                // state promises that we can and should dealloc
+                // we are the last Ptr accessible to the ptr of PtrCell, and we are dropped
+                // we carefully don't make any ref to PtrCell when calling dealloc
+                unsafe {
+                    dealloc(ptr, layout);
+                }
+            } else {
+                // SAFETY:
+                // state promises that we can and should dealloc
+                // we are the last Ptr accessible to the ptr of PtrCell, and we are dropped
+                // we carefully don't make any ref to PtrCell when calling dealloc
+                unsafe {
+                    dealloc(self.ptr.as_ptr().cast(), layout);
+                }
             }
         }
     }
 
+    // SAFETY: data is the data ptr of a StateCell and align is the alignment of data
+    pub(crate) unsafe fn from_data_ptr(data: *const D, align: usize) -> Self {
+        let offset = Layout::new::<StateCell<()>>().size().next_multiple_of(align);
+        // SAFETY: data is at offset of the StateCell
+        let ptr = unsafe { data.byte_sub(offset) } as *mut StateCell<D>;
+        // SAFETY: ptr is a StateCell
+        let ptr = unsafe { NonNull::new_unchecked(ptr) };
+        Ptr { ptr, phantom: PhantomData }
+    }
+
+    pub(crate) fn addr(&self) -> *const () {
+        self.ptr.as_ptr().cast_const().cast()
+    }
+
     pub(crate) fn cell(&self) -> &StateCell<D> {
         // SAFETY: when self is alive, ptr is always valid, and we never call ptr.as_mut()
         unsafe { self.ptr.as_ref() }
     }
 }
 
+// return the layout of the allocation and the offset of the StateCell
+pub(crate) fn layout_in<A>(cell: Layout) -> (Layout, usize) {
+    let (prefix, _) = Layout::new::<A>().extend(Layout::new::<Dealloc>()).unwrap();
+    let (layout, offset) = prefix.extend(cell).unwrap();
+    (layout.pad_to_align(), offset)
+}
+
+// the fields before data don't depend on the type of data
+#[cfg(feature = "dirty")]
+// SAFETY: addr is the addr of a living StateCell, which is alive during 'a
+pub(crate) unsafe fn header<'a>(addr: *const ()) -> &'a StateCell<()> {
+    // SAFETY: the allocation is larger than StateCell<()> and aligned to it
+    unsafe { &*addr.cast::<StateCell<()>>() }
+}
+
+// SAFETY: cell is allocated by Ptr::new_in with allocator A and the layout of cell
+unsafe fn dealloc_in<A: Allocator>(cell: NonNull<u8>, layout: Layout) {
+    let (layout, offset) = layout_in::<A>(layout);
+    // SAFETY: cell is at offset of the allocation
+    let base = unsafe { cell.sub(offset) };
+    // SAFETY: the allocator is at the start of the allocation and won't be accessed anymore
+    let alloc = unsafe { base.cast::<A>().read() };
+    // SAFETY: the allocation is allocated by alloc with layout
+    unsafe {
+        alloc.deallocate(base, layout);
+    }
+}
+
 impl<D: ?Sized> Debug for Ptr<D> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         self.ptr.fmt(f)
     }
 }
@@ -115,48 +370,313 @@ impl<D: ?Sized> Hash for Ptr<D> {
     }
 }
 
+impl<D: ?Sized> PartialOrd for Ptr<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<D: ?Sized> Ord for Ptr<D> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.addr().cmp(&other.addr())
+    }
+}
+
+// data is the last field, so the offset of data only depends on the alignment of data
+#[repr(C)]
 pub(crate) struct StateCell<D: ?Sized> {
     state: Cell<State>,
+    #[cfg(any(feature = "revocable", feature = "subscribe"))]
+    extra: Cell<Option<NonNull<Extra>>>,
+    // the sign bit indicates whether data is dirty
+    // other bits indicates the cnt of mutable access by owners, which wraps around
+    #[cfg(feature = "dirty")]
+    modification: Cell<usize>,
     data: UnsafeCell<D>,
 }
 
 impl<D: ?Sized> StateCell<D> {
     fn new(data: D, state: State) -> Self
     where D: Sized {
-        StateCell { state: Cell::new(state), data: UnsafeCell::new(data) }
+        StateCell {
+            state: Cell::new(state),
+            #[cfg(any(feature = "revocable", feature = "subscribe"))]
+            extra: Cell::new(None),
+            #[cfg(feature = "dirty")]
+            modification: Cell::new(0),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    // the cell itself is a holder
+    pub(crate) fn new_in_place(data: D) -> Self
+    where D: Sized {
+        Self::new(data, State::new_holder())
     }
 
     pub(crate) fn state(&self) -> State {
         self.state.get()
     }
 
-    fn clone_to_holder(&self) {
+    pub(crate) fn clone_to_holder(&self) {
         self.state.set(self.state.get().clone_to_holder());
     }
 
-    fn clone_to_viewer(&self) -> Result<(), State> {
+    pub(crate) fn clone_to_viewer(&self) -> Result<(), State> {
         self.state.set(self.state.get().clone_to_viewer()?);
         Ok(())
     }
 
-    fn clone_to_owner(&self) -> Result<(), State> {
+    pub(crate) fn clone_to_owner(&self) -> Result<(), State> {
         self.state.set(self.state.get().clone_to_owner()?);
         Ok(())
     }
 
-    fn drop_from_holder(&self) {
+    fn clone_to_frozen(&self) {
+        self.state.set(self.state.get().clone_to_frozen());
+    }
+
+    fn freeze(&self) {
+        self.state.set(self.state.get().freeze());
+    }
+
+    #[cfg(feature = "revocable")]
+    fn clone_to_revocable(&self, owned: bool) -> Result<(), State> {
+        let state = self.state.get();
+        let state = if owned { state.clone_to_owner()? } else { state.clone_to_viewer()? };
+        self.state.set(state.clone_to_holder());
+        let extra = self.extra();
+        let mut revocation = extra.revocation.get();
+        if owned {
+            revocation.owned = true;
+        } else {
+            revocation.viewer_cnt += 1;
+        }
+        extra.revocation.set(revocation);
+        Ok(())
+    }
+
+    pub(crate) fn drop_from_holder(&self) {
         self.state.set(self.state.get().drop_from_holder());
         self.check_drop_data();
     }
 
-    fn drop_from_viewer(&self) {
+    // the following transitions change the access level of a handle in place
+    // data is never dropped during them
+
+    pub(crate) fn holder_to_viewer(&self) -> Result<(), State> {
+        self.state.set(self.state.get().clone_to_viewer()?.drop_from_holder());
+        Ok(())
+    }
+
+    pub(crate) fn holder_to_owner(&self) -> Result<(), State> {
+        self.state.set(self.state.get().clone_to_owner()?.drop_from_holder());
+        Ok(())
+    }
+
+    pub(crate) fn viewer_to_holder(&self) {
+        self.state.set(self.state.get().drop_from_viewer().clone_to_holder());
+    }
+
+    // only succeed when there is no other viewer
+    pub(crate) fn viewer_to_owner(&self) -> Result<(), State> {
+        let state = self.state.get();
+        if state.viewer_count() != 1 {
+            return Err(state);
+        }
+        self.state.set(state.drop_from_viewer().clone_to_owner().map_err(|_| state)?);
+        Ok(())
+    }
+
+    pub(crate) fn owner_to_holder(&self) {
+        self.state.set(self.state.get().drop_from_owner().clone_to_holder());
+        #[cfg(feature = "subscribe")]
+        self.notify(Change::Released);
+    }
+
+    pub(crate) fn owner_to_viewer(&self) {
+        self.state.set(self.state.get().drop_from_owner().clone_to_viewer().unwrap());
+        #[cfg(feature = "subscribe")]
+        self.notify(Change::Released);
+    }
+
+    #[cfg(feature = "revocable")]
+    pub(crate) fn owner_to_revocable(&self) {
+        self.state.set(self.state.get().clone_to_holder());
+        let extra = self.extra();
+        let mut revocation = extra.revocation.get();
+        revocation.owned = true;
+        extra.revocation.set(revocation);
+    }
+
+    pub(crate) fn drop_from_viewer(&self) {
         self.state.set(self.state.get().drop_from_viewer());
         self.check_drop_data();
     }
 
-    fn drop_from_owner(&self) {
+    pub(crate) fn drop_from_owner(&self) {
         self.state.set(self.state.get().drop_from_owner());
         self.check_drop_data();
+        #[cfg(feature = "subscribe")]
+        self.notify(self.release_change());
+    }
+
+    #[cfg(feature = "revocable")]
+    fn drop_from_revocable(&self, epoch: usize, owned: bool) {
+        let extra = self.extra();
+        let mut revocation = extra.revocation.get();
+        #[cfg(feature = "subscribe")]
+        let released = owned && revocation.epoch == epoch;
+        if revocation.epoch == epoch {
+            if owned {
+                revocation.owned = false;
+                self.state.set(self.state.get().drop_from_owner());
+            } else {
+                revocation.viewer_cnt -= 1;
+                self.state.set(self.state.get().drop_from_viewer());
+            }
+            extra.revocation.set(revocation);
+        }
+        self.state.set(self.state.get().drop_from_holder());
+        self.check_drop_data();
+        #[cfg(feature = "subscribe")]
+        if released {
+            self.notify(self.release_change());
+        }
+    }
+
+    #[cfg(feature = "revocable")]
+    pub(crate) fn revoke_all(&self) -> Result<(), State> {
+        let Some(extra) = self.extra.get() else {
+            return Ok(());
+        };
+        // SAFETY: extra is valid until dealloc
+        let extra = unsafe { extra.as_ref() };
+        let revocation = extra.revocation.get();
+        if revocation.borrow_cnt != 0 {
+            return Err(self.state.get());
+        }
+        self.state.set(self.state.get().revoke(revocation));
+        extra.revocation.set(Revocation { epoch: revocation.epoch + 1, ..Revocation::default() });
+        self.check_drop_data();
+        #[cfg(feature = "subscribe")]
+        if revocation.owned {
+            self.notify(self.release_change());
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "revocable")]
+    pub(crate) fn epoch(&self) -> usize {
+        self.extra().revocation.get().epoch
+    }
+
+    #[cfg(feature = "revocable")]
+    pub(crate) fn borrow_revocable(&self, epoch: usize) -> Result<(), State> {
+        let extra = self.extra();
+        let mut revocation = extra.revocation.get();
+        if revocation.epoch != epoch {
+            return Err(self.state.get());
+        }
+        revocation.borrow_cnt += 1;
+        extra.revocation.set(revocation);
+        Ok(())
+    }
+
+    #[cfg(feature = "revocable")]
+    pub(crate) fn return_revocable(&self) {
+        let extra = self.extra();
+        let mut revocation = extra.revocation.get();
+        revocation.borrow_cnt -= 1;
+        extra.revocation.set(revocation);
+    }
+
+    // called by owners before giving out mutable refs of data
+    // moving out, dropping and reinitializing data also count as modification
+    pub(crate) fn modify(&self) {
+        #[cfg(feature = "dirty")]
+        self.modification.set(self.modification.get().wrapping_add(1) | DIRTY);
+    }
+
+    #[cfg(feature = "dirty")]
+    pub(crate) fn modification_count(&self) -> usize {
+        self.modification.get() & !DIRTY
+    }
+
+    #[cfg(feature = "dirty")]
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.modification.get() & DIRTY != 0
+    }
+
+    #[cfg(feature = "dirty")]
+    pub(crate) fn clear_dirty(&self) {
+        self.modification.set(self.modification.get() & !DIRTY);
+    }
+
+    #[cfg(feature = "subscribe")]
+    pub(crate) fn subscribe(&self, callback: Callback) -> usize {
+        let extra = self.extra();
+        let id = extra.next_subscriber.get();
+        extra.next_subscriber.set(id + 1);
+        extra.subscribers.borrow_mut().push((id, callback));
+        id
+    }
+
+    #[cfg(feature = "subscribe")]
+    pub(crate) fn unsubscribe(&self, id: usize) {
+        let mut subscribers = self.extra().subscribers.borrow_mut();
+        let index = subscribers.iter().position(|(i, _)| *i == id);
+        let callback = index.map(|index| subscribers.remove(index));
+        // the callback may own a subscription, which unsubscribes when dropped
+        drop(subscribers);
+        drop(callback);
+    }
+
+    // callbacks may subscribe, unsubscribe or access the data through their own handles
+    #[cfg(feature = "subscribe")]
+    fn notify(&self, change: Change) {
+        let Some(extra) = self.extra.get() else {
+            return;
+        };
+        // SAFETY: extra is valid until dealloc
+        let extra = unsafe { extra.as_ref() };
+        let callbacks: Vec<_> =
+            extra.subscribers.borrow().iter().map(|(_, callback)| Rc::clone(callback)).collect();
+        if callbacks.is_empty() {
+            return;
+        }
+        // callbacks may drop the last handle, so we keep the cell alive until they return
+        self.clone_to_holder();
+        for callback in callbacks {
+            callback(change);
+        }
+        self.drop_from_holder();
+    }
+
+    #[cfg(feature = "subscribe")]
+    fn release_change(&self) -> Change {
+        if self.state.get().is_dropped() { Change::Dropped } else { Change::Released }
+    }
+
+    #[cfg(any(feature = "revocable", feature = "subscribe"))]
+    fn extra(&self) -> &Extra {
+        let extra = if let Some(extra) = self.extra.get() {
+            extra
+        } else {
+            let extra = NonNull::from_mut(Box::leak(Box::<Extra>::default()));
+            self.extra.set(Some(extra));
+            extra
+        };
+        // SAFETY: extra is valid until dealloc
+        unsafe { extra.as_ref() }
+    }
+
+    #[cfg(any(feature = "revocable", feature = "subscribe"))]
+    fn drop_extra(&self) {
+        if let Some(extra) = self.extra.take() {
+            // SAFETY: extra is made by Box::leak and we take it so it won't be accessed anymore
+            drop(unsafe { Box::from_raw(extra.as_ptr()) });
+        }
     }
 
     fn check_drop_data(&self) {
@@ -170,13 +690,43 @@ impl<D: ?Sized> StateCell<D> {
     pub(crate) unsafe fn move_data(&self) -> D
     where D: Sized {
         self.state.set(self.state.get().drop());
+        self.modify();
         // SAFETY: call only once and there is no ref
         unsafe { ptr::read(self.data.get()) }
     }
 
+    // SAFETY: call only once and there is no ref
+    // with_addr makes a ptr at the addr with the metadata of the ptr
+    pub(crate) unsafe fn move_data_to_box(
+        &self, with_addr: fn(*mut D, *mut u8) -> *mut D,
+    ) -> Box<D> {
+        let data = self.data.get();
+        // SAFETY: data hasn't been dropped
+        let layout = Layout::for_value(unsafe { &*data });
+        let ptr = if layout.size() == 0 {
+            ptr::without_provenance_mut(layout.align())
+        } else {
+            // SAFETY: layout has non-zero size
+            let ptr = unsafe { alloc(layout) };
+            if ptr.is_null() {
+                handle_alloc_error(layout);
+            }
+            ptr
+        };
+        self.state.set(self.state.get().drop());
+        self.modify();
+        // SAFETY: ptr is valid for writes of the data and doesn't overlap with data
+        unsafe {
+            ptr::copy_nonoverlapping(data.cast::<u8>(), ptr, layout.size());
+        }
+        // SAFETY: ptr is allocated by the global allocator with the layout of data
+        unsafe { Box::from_raw(with_addr(data, ptr)) }
+    }
+
     // SAFETY: call only once and there is no ref
     pub(crate) unsafe fn drop_data(&self) {
         self.state.set(self.state.get().drop());
+        self.modify();
         // SAFETY: call only once and there is no ref
         unsafe {
             ptr::drop_in_place(self.data.get());
@@ -187,16 +737,33 @@ impl<D: ?Sized> StateCell<D> {
     pub(crate) unsafe fn reinit_data(&self, d: D)
     where D: Sized {
         self.state.set(self.state.get().reinit());
+        self.modify();
         // SAFETY: data is dropped
         unsafe {
             ptr::write(self.data.get(), d);
         }
+        #[cfg(feature = "subscribe")]
+        self.notify(Change::Reinit);
     }
 
     fn should_dealloc(&self) -> bool {
         self.state.get().should_dealloc()
     }
 
+    #[cfg(all(test, feature = "compact-state"))]
+    pub(crate) fn set_holder_count(&self, cnt: usize) {
+        let mut state = self.state.get();
+        state.holder_cnt = state.holder_cnt & !HOLDER_CNT | cnt as Cnt;
+        self.state.set(state);
+    }
+
+    #[cfg(all(test, feature = "compact-state"))]
+    pub(crate) fn set_viewer_count(&self, cnt: usize) {
+        let mut state = self.state.get();
+        state.viewer_cnt = cnt as Cnt;
+        self.state.set(state);
+    }
+
     // SAFETY: make sure data not dropped and there is no mut ref
     pub(crate) unsafe fn deref<'a>(&self) -> &'a D {
         // SAFETY: make sure data not dropped and there is no mut ref
@@ -215,16 +782,24 @@ impl<D: ?Sized> StateCell<D> {
 }
 
 impl State {
+    pub const MAX_HOLDER_COUNT: usize = HOLDER_CNT as usize;
+
+    pub const MAX_VIEWER_COUNT: usize = Cnt::MAX as usize;
+
     pub fn is_dropped(&self) -> bool {
         self.holder_cnt < 0
     }
 
+    pub fn is_frozen(&self) -> bool {
+        self.holder_cnt & FROZEN != 0
+    }
+
     pub fn holder_count(&self) -> usize {
-        (self.holder_cnt & isize::MAX) as usize
+        (self.holder_cnt & HOLDER_CNT) as usize
     }
 
     pub fn viewer_count(&self) -> usize {
-        (self.viewer_cnt & isize::MAX) as usize
+        (self.viewer_cnt & Cnt::MAX) as usize
     }
 
     pub fn is_owned(&self) -> bool {
@@ -240,10 +815,11 @@ impl State {
     }
 
     fn new_owner() -> Self {
-        Self { holder_cnt: 0, viewer_cnt: isize::MIN }
+        Self { holder_cnt: 0, viewer_cnt: Cnt::MIN }
     }
 
     fn clone_to_holder(mut self) -> Self {
+        assert!(self.holder_count() < Self::MAX_HOLDER_COUNT, "holder count overflow");
         self.holder_cnt += 1;
         self
     }
@@ -252,16 +828,24 @@ impl State {
         if self.is_dropped() || self.is_owned() {
             Err(self)
         } else {
+            assert!(self.viewer_count() < Self::MAX_VIEWER_COUNT, "viewer count overflow");
             self.viewer_cnt += 1;
             Ok(self)
         }
     }
 
+    // only for frozen data, which can't be owned
+    fn clone_to_frozen(mut self) -> Self {
+        assert!(self.viewer_count() < Self::MAX_VIEWER_COUNT, "viewer count overflow");
+        self.viewer_cnt += 1;
+        self
+    }
+
     fn clone_to_owner(mut self) -> Result<Self, Self> {
-        if self.is_dropped() || self.viewer_cnt != 0 {
+        if self.is_dropped() || self.is_frozen() || self.viewer_cnt != 0 {
             Err(self)
         } else {
-            self.viewer_cnt = isize::MIN;
+            self.viewer_cnt = Cnt::MIN;
             Ok(self)
         }
     }
@@ -281,33 +865,95 @@ impl State {
         self
     }
 
+    fn freeze(mut self) -> Self {
+        self.holder_cnt |= FROZEN;
+        self
+    }
+
+    #[cfg(feature = "revocable")]
+    fn revoke(mut self, revocation: Revocation) -> Self {
+        if revocation.owned {
+            self.viewer_cnt = 0;
+        } else {
+            self.viewer_cnt -= revocation.viewer_cnt as Cnt;
+        }
+        self
+    }
+
+    fn alloc(mut self) -> Self {
+        self.holder_cnt |= ALLOC;
+        self
+    }
+
+    fn has_alloc(self) -> bool {
+        self.holder_cnt & ALLOC != 0
+    }
+
+    fn local(mut self, local: bool) -> Self {
+        if local {
+            self.holder_cnt |= LOCAL;
+        }
+        self
+    }
+
+    // handles of a local allocation can't be sent to another thread
+    pub(crate) fn is_local(self) -> bool {
+        self.holder_cnt & LOCAL != 0
+    }
+
+    fn fields(self) -> (bool, usize, usize, bool, bool) {
+        (
+            self.is_dropped(),
+            self.holder_count(),
+            self.viewer_count(),
+            self.is_owned(),
+            self.is_frozen(),
+        )
+    }
+
     fn drop(mut self) -> Self {
-        self.holder_cnt |= isize::MIN;
+        self.holder_cnt |= Cnt::MIN;
         self
     }
 
     fn reinit(mut self) -> Self {
-        self.holder_cnt &= isize::MAX;
+        self.holder_cnt &= Cnt::MAX;
         self
     }
 
     // if already dropped, return false
-    fn should_drop(&self) -> bool {
-        self.holder_cnt == 0 && self.viewer_cnt == 0
+    fn should_drop(self) -> bool {
+        !self.is_dropped() && self.holder_count() == 0 && self.viewer_cnt == 0
     }
 
-    fn should_dealloc(&self) -> bool {
-        self.holder_cnt == isize::MIN && self.viewer_cnt == 0
+    fn should_dealloc(self) -> bool {
+        self.is_dropped() && self.holder_count() == 0 && self.viewer_cnt == 0
+    }
+}
+
+// the allocator bits aren't visible, so they are ignored like in Debug
+impl PartialEq for State {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields() == other.fields()
+    }
+}
+
+impl Eq for State {}
+
+impl Hash for State {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.fields().hash(state);
     }
 }
 
 impl Debug for State {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("State")
             .field("dropped", &self.is_dropped())
             .field("holder", &self.holder_count())
             .field("viewer", &self.viewer_count())
             .field("owned", &self.is_owned())
+            .field("frozen", &self.is_frozen())
             .finish()
     }
 }
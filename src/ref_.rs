@@ -1,9 +1,10 @@
-use std::fmt::Debug;
-use std::fmt::Formatter;
-use std::hash::Hash;
-use std::hash::Hasher;
-use std::ptr;
-use std::ptr::NonNull;
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::ptr;
+use core::ptr::NonNull;
 
 use crate::ptr::Ptr;
 
@@ -90,7 +91,7 @@ impl<Source: ?Sized> Ref<Source, Source> {
 }
 
 impl<Source: ?Sized, Target: ?Sized> Debug for Ref<Source, Target> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_tuple("Ref").field(&self.source).field(&self.target).finish()
     }
 }
@@ -109,3 +110,17 @@ impl<Source: ?Sized, Target: ?Sized> Hash for Ref<Source, Target> {
         self.target.hash(state);
     }
 }
+
+impl<Source: ?Sized, Target: ?Sized> PartialOrd for Ref<Source, Target> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized> Ord for Ref<Source, Target> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let target = self.target.as_ptr().cast::<()>();
+        let other_target = other.target.as_ptr().cast::<()>();
+        self.source.cmp(&other.source).then(target.cmp(&other_target))
+    }
+}
@@ -0,0 +1,137 @@
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::mem;
+use core::ops::Deref;
+use core::ops::DerefMut;
+
+use crate::Holder;
+use crate::Owner;
+use crate::State;
+use crate::Viewer;
+use crate::ptr::Ptr;
+
+pub struct Revocable<D: ?Sized> {
+    ptr: Ptr<D>,
+    epoch: usize,
+    owned: bool,
+}
+
+pub struct RevocableRef<'a, D: ?Sized> {
+    revocable: &'a Revocable<D>,
+}
+
+pub struct RevocableMut<'a, D: ?Sized> {
+    revocable: &'a mut Revocable<D>,
+}
+
+impl<D: ?Sized> Revocable<D> {
+    pub fn state(&self) -> State {
+        self.ptr.cell().state()
+    }
+
+    pub fn is_owner(&self) -> bool {
+        self.owned
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.ptr.cell().epoch() != self.epoch
+    }
+
+    pub fn get(&self) -> Result<RevocableRef<'_, D>, State> {
+        self.ptr.cell().borrow_revocable(self.epoch)?;
+        Ok(RevocableRef { revocable: self })
+    }
+
+    pub fn get_mut(&mut self) -> Result<RevocableMut<'_, D>, State> {
+        if !self.owned {
+            return Err(self.state());
+        }
+        self.ptr.cell().borrow_revocable(self.epoch)?;
+        Ok(RevocableMut { revocable: self })
+    }
+
+    fn new(ptr: Ptr<D>, owned: bool) -> Self {
+        let epoch = ptr.cell().epoch();
+        Self { ptr, epoch, owned }
+    }
+}
+
+impl<D: ?Sized> Drop for Revocable<D> {
+    fn drop(&mut self) {
+        self.ptr.drop_from_revocable(self.epoch, self.owned);
+    }
+}
+
+impl<D: ?Sized> From<Viewer<D>> for Revocable<D> {
+    fn from(value: Viewer<D>) -> Self {
+        let holder = Holder::from(value);
+        Self::new(Holder::ptr(&holder).clone_to_revocable(false).unwrap(), false)
+    }
+}
+
+impl<D: ?Sized> From<Owner<D>> for Revocable<D> {
+    fn from(value: Owner<D>) -> Self {
+        let ptr = Owner::ptr(&value).transfer();
+        mem::forget(value);
+        ptr.cell().owner_to_revocable();
+        Self::new(ptr, true)
+    }
+}
+
+impl<D: ?Sized> Debug for Revocable<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Revocable")
+            .field("ptr", &self.ptr)
+            .field("owned", &self.owned)
+            .field("revoked", &self.is_revoked())
+            .finish()
+    }
+}
+
+impl<D: ?Sized> Deref for RevocableRef<'_, D> {
+    type Target = D;
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the revocable handle hasn't been revoked and can't be revoked when self is alive
+        unsafe { self.revocable.ptr.cell().deref() }
+    }
+}
+
+impl<D: ?Sized> Drop for RevocableRef<'_, D> {
+    fn drop(&mut self) {
+        self.revocable.ptr.cell().return_revocable();
+    }
+}
+
+impl<D: ?Sized> Deref for RevocableMut<'_, D> {
+    type Target = D;
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the revocable handle hasn't been revoked and can't be revoked when self is alive
+        unsafe { self.revocable.ptr.cell().deref() }
+    }
+}
+
+impl<D: ?Sized> DerefMut for RevocableMut<'_, D> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.revocable.ptr.cell().modify();
+        // SAFETY: the revocable handle hasn't been revoked and can't be revoked when self is alive
+        unsafe { self.revocable.ptr.cell().deref_mut() }
+    }
+}
+
+impl<D: ?Sized> Drop for RevocableMut<'_, D> {
+    fn drop(&mut self) {
+        self.revocable.ptr.cell().return_revocable();
+    }
+}
+
+impl<D: ?Sized + Debug> Debug for RevocableRef<'_, D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<D: ?Sized + Debug> Debug for RevocableMut<'_, D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
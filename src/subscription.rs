@@ -0,0 +1,44 @@
+use core::fmt::Debug;
+use core::fmt::Formatter;
+
+use crate::Holder;
+
+// the kind of change passed to the callbacks of subscriptions
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Change {
+    // the last owner released the data, which may have been modified
+    Released,
+    // a holder reinitialized the dropped data
+    Reinit,
+    // an owner dropped or moved out the data
+    Dropped,
+}
+
+// the callback is removed when the subscription is dropped
+// the subscription is a holder, so the allocation stays alive until it is dropped
+pub struct Subscription<D: ?Sized> {
+    holder: Holder<D>,
+    id: usize,
+}
+
+impl<D: ?Sized> Subscription<D> {
+    pub(crate) fn new(holder: Holder<D>, id: usize) -> Self {
+        Self { holder, id }
+    }
+
+    pub fn holder(&self) -> &Holder<D> {
+        &self.holder
+    }
+}
+
+impl<D: ?Sized> Drop for Subscription<D> {
+    fn drop(&mut self) {
+        Holder::ptr(&self.holder).cell().unsubscribe(self.id);
+    }
+}
+
+impl<D: ?Sized> Debug for Subscription<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Subscription").field("holder", &self.holder).field("id", &self.id).finish()
+    }
+}
@@ -1,12 +1,61 @@
+use std::alloc::Layout;
+use std::borrow::ToOwned;
+use std::boxed::Box;
+#[cfg(feature = "subscribe")]
+use std::cell::Cell;
+#[cfg(feature = "subscribe")]
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::ops::Deref;
 use std::ops::DerefMut;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::ptr::NonNull;
+use std::rc::Rc;
+use std::string::String;
+use std::sync::Arc;
+use std::sync::atomic;
+use std::sync::atomic::AtomicUsize;
+use std::thread;
+use std::vec;
+use std::vec::Vec;
 
+use allocator_api2::alloc::AllocError;
+use allocator_api2::alloc::Allocator;
+use allocator_api2::alloc::Global;
+
+use crate::Access;
+use crate::AccessLevel;
+use crate::AllocId;
+use crate::ByValue;
+use crate::CellHolder;
+use crate::CellOwner;
+use crate::CellViewer;
+#[cfg(feature = "subscribe")]
+use crate::Change;
+use crate::CowViewer;
+use crate::Frozen;
+use crate::Handle;
+use crate::HandleBundle;
+#[cfg(feature = "dirty")]
+use crate::History;
 use crate::Holder;
+use crate::OwnCell;
 use crate::Owner;
 use crate::OwnerRef;
+use crate::Pool;
+use crate::ReadHandle;
+#[cfg(feature = "dirty")]
+use crate::Revision;
+#[cfg(feature = "revocable")]
+use crate::Revocable;
 use crate::State;
+use crate::Transaction;
+use crate::UniqueOwner;
 use crate::Viewer;
 use crate::ViewerRef;
+use crate::WriteHandle;
+use crate::ptr::StateCell;
 
 #[test]
 fn test_example_owner_viewer_holder() -> Result<(), State> {
@@ -256,3 +305,955 @@ fn test_circular() -> Result<(), State> {
 
     Ok(())
 }
+
+#[test]
+fn test_unique_owner() -> Result<(), State> {
+    let o = Owner::new("hello".to_owned());
+    let h = Holder::from(&o);
+    let o = Owner::try_into_unique(o).unwrap_err();
+    drop(h);
+    let mut u = Owner::try_into_unique(o).unwrap();
+    assert_state(UniqueOwner::state(&u), false, 0, 0, true);
+    u.push_str(" world");
+    let u = thread::spawn(move || {
+        let mut u = u;
+        u.push('!');
+        u
+    })
+    .join()
+    .unwrap();
+    let h = Holder::from(u);
+    assert_state(Holder::state(&h), false, 1, 0, false);
+    let v = Viewer::try_from(&h)?;
+    assert_eq!(v.deref(), "hello world!");
+    Ok(())
+}
+
+#[test]
+fn test_handle_bundle() -> Result<(), State> {
+    let o = Owner::new(("hello".to_owned(), 1));
+    let h1 = Holder::from(&o);
+    let v = Viewer::new(2);
+    let h2 = Holder::from(&v);
+    let o = OwnerRef::map(OwnerRef::from(o), |t| &mut t.0);
+
+    let mut bundle = HandleBundle::new();
+    bundle.push(o);
+    bundle.push(h1);
+    bundle.push(v);
+    assert!(!bundle.is_complete());
+    let mut bundle = bundle.try_into_send().unwrap_err();
+    bundle.push(h2);
+    assert!(bundle.is_complete());
+    let bundle = bundle.try_into_send().unwrap();
+
+    let bundle = thread::spawn(move || {
+        let mut bundle = HandleBundle::from(bundle);
+        let mut o = bundle.take::<OwnerRef<(String, i32), String>>().unwrap();
+        o.push_str(" world");
+        let h1 = Holder::from(o);
+        bundle.push(h1);
+        bundle.try_into_send().unwrap()
+    })
+    .join()
+    .unwrap();
+
+    let mut bundle = HandleBundle::from(bundle);
+    assert_eq!(bundle.len(), 4);
+    let h1 = bundle.take::<Holder<(String, i32)>>().unwrap();
+    let v1 = Viewer::try_from(&h1)?;
+    assert_eq!(v1.0, "hello world");
+    let v = bundle.take::<Viewer<i32>>().unwrap();
+    assert_eq!(*v, 2);
+    assert!(bundle.take::<Viewer<i32>>().is_none());
+    assert_eq!(bundle.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_frozen() -> Result<(), State> {
+    let o = Owner::new("frozen".to_owned());
+    let h = Holder::from(&o);
+    let f1 = Owner::freeze(o);
+    assert!(Frozen::state(&f1).is_frozen());
+    assert_state(Frozen::state(&f1), false, 1, 1, false);
+    let f2 = Frozen::clone(&f1);
+    assert_state(Frozen::state(&f1), false, 1, 2, false);
+    let v = Viewer::try_from(&h)?;
+    assert_eq!(v.deref(), "frozen");
+    assert_eq!(f2.deref(), "frozen");
+    drop(v);
+    drop(f1);
+    drop(f2);
+    assert_state(Holder::state(&h), false, 1, 0, false);
+    Owner::try_from(&h).unwrap_err();
+    OwnerRef::try_from(&h).unwrap_err();
+    let f3 = Frozen::try_from(&h)?;
+    assert_eq!(f3.deref(), "frozen");
+    let v = Viewer::from(f3);
+    Frozen::try_from(&v)?;
+    Frozen::try_from(&Holder::new(1)).unwrap_err();
+    Ok(())
+}
+
+#[cfg(feature = "revocable")]
+#[test]
+fn test_revocable() -> Result<(), State> {
+    let h = Holder::new("plugin".to_owned());
+    let r1 = Revocable::from(Viewer::try_from(&h)?);
+    let mut r2 = Revocable::from(Viewer::try_from(&h)?);
+    assert_state(Holder::state(&h), false, 3, 2, false);
+    assert_eq!(r1.get()?.deref(), "plugin");
+    r2.get_mut().unwrap_err();
+    {
+        let _r = r1.get()?;
+        Holder::revoke_all(&h).unwrap_err();
+        assert!(!r1.is_revoked());
+    }
+    Owner::try_from(&h).unwrap_err();
+    Holder::revoke_all(&h)?;
+    assert!(r1.is_revoked());
+    assert_state(Holder::state(&h), false, 3, 0, false);
+    r1.get().unwrap_err();
+    let mut o = Owner::try_from(&h)?;
+    o.push_str(" revoked");
+    drop(r1);
+    assert_state(Holder::state(&h), false, 2, 0, true);
+
+    let mut r3 = Revocable::from(o);
+    assert!(r3.is_owner());
+    r3.get_mut()?.push('!');
+    assert_eq!(r3.get()?.deref(), "plugin revoked!");
+    Viewer::try_from(&h).unwrap_err();
+    Holder::revoke_all(&h)?;
+    assert_state(Holder::state(&h), false, 3, 0, false);
+    r3.get_mut().unwrap_err();
+    let v = Viewer::try_from(&h)?;
+    assert_eq!(v.deref(), "plugin revoked!");
+    drop(r2);
+    drop(r3);
+    assert_state(Holder::state(&h), false, 1, 1, false);
+
+    let r4 = Revocable::from(v);
+    drop(h);
+    assert_eq!(r4.get()?.deref(), "plugin revoked!");
+    Ok(())
+}
+
+#[derive(Clone, Default)]
+struct CountAlloc {
+    cnt: Arc<AtomicUsize>,
+}
+
+impl CountAlloc {
+    fn count(&self) -> usize {
+        self.cnt.load(atomic::Ordering::Relaxed)
+    }
+}
+
+// SAFETY: forward to Global
+unsafe impl Allocator for CountAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.cnt.fetch_add(1, atomic::Ordering::Relaxed);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.cnt.fetch_sub(1, atomic::Ordering::Relaxed);
+        // SAFETY: forward to Global
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+}
+
+#[test]
+fn test_new_in() -> Result<(), State> {
+    #[repr(align(32))]
+    struct Aligned(u8);
+
+    let alloc = CountAlloc::default();
+    let o = Owner::new_in("alloc".to_owned(), alloc.clone());
+    let v = Viewer::new_in(Aligned(1), alloc.clone());
+    let h = Holder::new_in((), alloc.clone());
+    assert_eq!(alloc.count(), 3);
+    assert_eq!(Holder::state(&h), Holder::state(&Holder::new(())));
+    assert_eq!(v.0, 1);
+    assert_eq!((&raw const *v).addr() % 32, 0);
+    let h1 = Holder::from(o);
+    let o = Owner::try_from(&h1)?;
+    assert_eq!(o.deref(), "alloc");
+    Owner::drop_data(o);
+    assert_eq!(alloc.count(), 3);
+    drop(h1);
+    drop(v);
+    assert_eq!(alloc.count(), 1);
+    drop(h);
+    assert_eq!(alloc.count(), 0);
+
+    // the allocator is Send and Sync, so the last handle can dealloc in another thread
+    let u = Owner::try_into_unique(Owner::new_in(1, alloc.clone())).unwrap();
+    thread::spawn(move || drop(u)).join().unwrap();
+    assert_eq!(alloc.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_pool() -> Result<(), State> {
+    let pool = Pool::with_chunk_len(2);
+    let h1 = pool.holder("1".to_owned());
+    let v2 = pool.viewer("2".to_owned());
+    let o3 = pool.owner("3".to_owned());
+    assert_eq!(pool.chunk_count(), 2);
+    assert_eq!(Holder::state(&h1), Holder::state(&Holder::new(String::new())));
+    assert_ne!(Holder::from(&v2), Holder::from(&o3));
+    let h3 = Holder::from(o3);
+    let v3 = Viewer::try_from(&h3)?;
+    assert_eq!(v3.deref(), "3");
+    assert_eq!(v2.deref(), "2");
+    drop(v2);
+    drop(h1);
+    let o4 = pool.owner("4".to_owned());
+    let o5 = pool.owner("5".to_owned());
+    assert_eq!(pool.chunk_count(), 2);
+    assert_eq!(o4.deref(), "4");
+    assert_eq!(o5.deref(), "5");
+    drop(pool);
+    assert_eq!(o5.deref(), "5");
+
+    // the pool is shared by its handles without synchronization, so they stay in this thread
+    let o5 = Owner::try_into_unique(o5).unwrap_err();
+    let mut bundle = HandleBundle::new();
+    bundle.push(o5);
+    assert!(!bundle.is_complete());
+    bundle.try_into_send().unwrap_err();
+    Ok(())
+}
+
+#[test]
+fn test_own_cell() -> Result<(), State> {
+    let mut cell = OwnCell::new("cell".to_owned());
+    assert_state(cell.state(), false, 1, 0, false);
+    {
+        let mut o = cell.owner()?;
+        o.push_str(" owner");
+        let h = CellHolder::from(&o);
+        cell.viewer().unwrap_err();
+        assert_state(CellHolder::state(&h), false, 2, 0, true);
+        let v1 = CellViewer::from(o);
+        let v2 = CellViewer::clone(&v1);
+        assert_eq!(v1.deref(), "cell owner");
+        assert_eq!(v2.deref(), "cell owner");
+        CellOwner::try_from(&h).unwrap_err();
+        drop(v1);
+        let o = CellOwner::try_from(v2)?;
+        CellOwner::drop_data(o);
+        assert_state(cell.state(), true, 2, 0, false);
+        CellHolder::reinit(&h, "reinit".to_owned())?;
+    }
+    cell.get_mut().unwrap().push('!');
+    assert_eq!(cell.into_inner().unwrap(), "reinit!");
+
+    let payload = Rc::new(());
+    let cell = OwnCell::new(Rc::clone(&payload));
+    CellOwner::drop_data(cell.owner()?);
+    assert_eq!(Rc::strong_count(&payload), 1);
+    assert!(cell.into_inner().is_none());
+    let cell = OwnCell::new(Rc::clone(&payload));
+    drop(cell);
+    assert_eq!(Rc::strong_count(&payload), 1);
+
+    // the cell can be moved to another thread when no handle borrows it
+    let cell = OwnCell::new("send".to_owned());
+    CellOwner::drop_data(cell.owner()?);
+    let cell = thread::spawn(move || {
+        CellHolder::reinit(&cell.holder(), "sent".to_owned()).unwrap();
+        cell
+    })
+    .join()
+    .unwrap();
+    assert_eq!(*cell.viewer()?, "sent");
+    Ok(())
+}
+
+#[test]
+fn test_state_size() -> Result<(), State> {
+    #[cfg(not(feature = "compact-state"))]
+    assert_eq!(size_of::<State>(), 2 * size_of::<isize>());
+    #[cfg(feature = "compact-state")]
+    assert_eq!(size_of::<State>(), 2 * size_of::<u32>());
+    assert_eq!(State::MAX_HOLDER_COUNT, (1 << (8 * size_of::<State>() / 2 - 4)) - 1);
+    assert_eq!(State::MAX_VIEWER_COUNT, (1 << (8 * size_of::<State>() / 2 - 1)) - 1);
+    Ok(())
+}
+
+#[cfg(feature = "compact-state")]
+#[test]
+#[should_panic(expected = "holder count overflow")]
+fn test_holder_count_overflow() {
+    let h = Holder::new(());
+    // driving the count up to the limit takes too long
+    Holder::ptr(&h).cell().set_holder_count(State::MAX_HOLDER_COUNT - 1);
+    let _h1 = Holder::clone(&h);
+    let _h2 = Holder::clone(&h);
+}
+
+#[cfg(feature = "compact-state")]
+#[test]
+#[should_panic(expected = "viewer count overflow")]
+fn test_viewer_count_overflow() {
+    let v = Viewer::new(());
+    // driving the count up to the limit takes too long
+    Viewer::ptr(&v).cell().set_viewer_count(State::MAX_VIEWER_COUNT - 1);
+    let _v1 = Viewer::clone(&v);
+    let _v2 = Viewer::clone(&v);
+}
+
+#[test]
+fn test_zero_sized() -> Result<(), State> {
+    struct Marker;
+
+    // data of zero-sized type takes no space in the allocation
+    #[cfg(not(any(feature = "revocable", feature = "subscribe", feature = "dirty")))]
+    assert_eq!(size_of::<StateCell<Marker>>(), size_of::<State>());
+    // optional features add the extra ptr and the modification count
+    let words = usize::from(cfg!(any(feature = "revocable", feature = "subscribe")))
+        + usize::from(cfg!(feature = "dirty"));
+    assert_eq!(size_of::<StateCell<Marker>>(), size_of::<State>() + words * size_of::<usize>());
+    let o1 = Owner::new(Marker);
+    let o2 = Owner::new(Marker);
+    assert_ne!(o1, o2);
+    assert_ne!(&raw const *o1, &raw const *o2);
+    let h1 = Holder::from(o1);
+    let h2 = Holder::from(o2);
+    assert_ne!(h1, h2);
+    let v1 = Viewer::try_from(&h1)?;
+    Owner::try_from(&h1).unwrap_err();
+    let o2 = Owner::try_from(&h2)?;
+    Owner::drop_data(o2);
+    Viewer::try_from(&h2).unwrap_err();
+    drop(v1);
+    Ok(())
+}
+
+#[test]
+fn test_raw() -> Result<(), State> {
+    #[repr(align(64))]
+    struct Aligned(u8);
+
+    let o = Owner::new(Aligned(1));
+    let h = Holder::from(&o);
+    let raw = Owner::into_raw(o).cast::<()>();
+    assert_state(Holder::state(&h), false, 1, 0, true);
+    // SAFETY: raw is returned by Owner::into_raw
+    let mut o = unsafe { Owner::from_raw(raw.cast::<Aligned>()) };
+    o.0 = 2;
+    assert_eq!(Owner::as_ptr(&o), Holder::as_ptr(&h));
+    let v = Viewer::from(o);
+    let raw = Viewer::into_raw(v);
+    // SAFETY: there is a viewer count
+    unsafe {
+        Viewer::increment_count(raw);
+    }
+    assert_state(Holder::state(&h), false, 1, 2, false);
+    // SAFETY: there are two viewer counts
+    unsafe {
+        Viewer::decrement_count(raw);
+    }
+    assert_state(Holder::state(&h), false, 1, 1, false);
+    // SAFETY: there is a viewer count
+    let v = unsafe { Viewer::from_raw(raw) };
+    assert_eq!(v.0, 2);
+    drop(v);
+
+    let raw = Holder::into_raw(h);
+    // SAFETY: there is a holder count
+    unsafe {
+        Holder::increment_count(raw);
+    }
+    // SAFETY: there are two holder counts
+    let h1 = unsafe { Holder::from_raw(raw) };
+    // SAFETY: there is a holder count
+    let h2 = unsafe { Holder::from_raw(raw) };
+    assert_eq!(h1, h2);
+    assert_state(Holder::state(&h1), false, 2, 0, false);
+    Ok(())
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn test_ffi() {
+    use core::cell::Cell;
+    use core::ffi::c_void;
+    use core::ptr;
+
+    use crate::ffi::*;
+
+    unsafe extern "C" fn drop_box(data: *mut c_void) {
+        // SAFETY: data is made by Box::into_raw
+        drop(unsafe { Box::from_raw(data.cast::<Cell<i32>>()) });
+    }
+
+    let data = Box::into_raw(Box::new(Cell::new(1))).cast::<c_void>();
+    // SAFETY: drop_box can be called with data
+    let h = unsafe { rt_own_holder_new(data, Some(drop_box)) };
+    let mut o = ptr::null_mut();
+    // SAFETY: h is alive and o is valid for writes
+    assert_eq!(unsafe { rt_own_owner_acquire(h, &raw mut o) }, RtOwnError::Ok);
+    let mut v = ptr::null_mut();
+    // SAFETY: h is alive and v is valid for writes
+    assert_eq!(unsafe { rt_own_viewer_acquire(h, &raw mut v) }, RtOwnError::Owned);
+    // SAFETY: o is alive
+    unsafe {
+        rt_own_owner_release(o);
+    }
+    // SAFETY: h is alive and v is valid for writes
+    assert_eq!(unsafe { rt_own_viewer_acquire(h, &raw mut v) }, RtOwnError::Ok);
+    // SAFETY: v is alive
+    let h1 = unsafe { rt_own_viewer_holder(v) };
+    let mut state =
+        RtOwnState { dropped: true, owned: true, frozen: true, holder_count: 0, viewer_count: 0 };
+    // SAFETY: h1 is alive and state is valid for writes
+    assert_eq!(unsafe { rt_own_holder_state(h1, &raw mut state) }, RtOwnError::Ok);
+    assert_eq!(state, RtOwnState {
+        dropped: false,
+        owned: false,
+        frozen: false,
+        holder_count: 2,
+        viewer_count: 1,
+    });
+    // SAFETY: v is alive
+    let data = unsafe { rt_own_viewer_data(v) }.cast::<Cell<i32>>();
+    // SAFETY: data is alive when v is alive
+    assert_eq!(unsafe { &*data }.get(), 1);
+    // SAFETY: h1 is alive
+    unsafe {
+        rt_own_holder_release(h1);
+    }
+    // SAFETY: v is alive
+    unsafe {
+        rt_own_viewer_release(v);
+    }
+    // SAFETY: h is alive and o is valid for writes
+    assert_eq!(unsafe { rt_own_owner_acquire(h, &raw mut o) }, RtOwnError::Ok);
+    // SAFETY: o is alive
+    unsafe {
+        rt_own_owner_drop_data(o);
+    }
+    // SAFETY: h is alive and o is valid for writes
+    assert_eq!(unsafe { rt_own_owner_acquire(h, &raw mut o) }, RtOwnError::Dropped);
+    let data = Box::into_raw(Box::new(Cell::new(2))).cast::<c_void>();
+    // SAFETY: h is alive and drop_box can be called with data
+    assert_eq!(unsafe { rt_own_holder_reinit(h, data, Some(drop_box)) }, RtOwnError::Ok);
+    // SAFETY: h is alive
+    assert_eq!(unsafe { rt_own_holder_reinit(h, ptr::null_mut(), None) }, RtOwnError::NotDropped);
+    // SAFETY: null holders are rejected
+    let error = unsafe { rt_own_owner_acquire(ptr::null_mut(), &raw mut o) };
+    assert_eq!(error, RtOwnError::NullPointer);
+    // SAFETY: h is alive
+    unsafe {
+        rt_own_holder_release(h);
+    }
+}
+
+#[test]
+fn test_std_conversion() -> Result<(), State> {
+    let o = Owner::from(Box::new(1));
+    assert_eq!(*Owner::into_box(o), 1);
+
+    let o = Owner::<[String]>::from(vec!["a".to_owned(), "b".to_owned()].into_boxed_slice());
+    let h = Holder::from(&o);
+    assert_eq!(o.len(), 2);
+    let b = Box::<[String]>::from(o);
+    assert_eq!(&*b, ["a", "b"]);
+    assert_state(Holder::state(&h), true, 1, 0, false);
+
+    let o = Owner::<str>::from(Box::<str>::from("abc"));
+    assert_eq!(&*o, "abc");
+    assert_eq!(&*Box::<str>::from(o), "abc");
+
+    let o = Owner::<[()]>::from(vec![(); 3]);
+    assert_eq!(Vec::from(o).len(), 3);
+
+    let rc = Rc::new(1);
+    let rc1 = Rc::clone(&rc);
+    let rc = Owner::try_from(rc).unwrap_err();
+    drop(rc1);
+    let o = Owner::try_from(rc).unwrap();
+    assert_eq!(*o, 1);
+
+    let h = Holder::new(String::from("a"));
+    let h1 = h.clone();
+    let h = Holder::try_into_inner(h).unwrap_err();
+    drop(h1);
+    let v = Viewer::try_from(&h)?;
+    let h = Holder::try_into_inner(h).unwrap_err();
+    let v1 = v.clone();
+    let v = Viewer::try_unwrap(v).unwrap_err();
+    drop(v1);
+    let v = Viewer::try_unwrap(v).unwrap_err();
+    drop(h);
+    assert_eq!(Viewer::try_unwrap(v).unwrap(), "a");
+    assert_eq!(Holder::try_into_inner(Holder::new(1)).unwrap(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_leak() -> Result<(), State> {
+    let o = Owner::new(String::from("a"));
+    let h = Holder::from(&o);
+    let s = Owner::leak(o);
+    s.push('b');
+    assert_state(Holder::state(&h), false, 1, 0, true);
+    Viewer::try_from(&h).unwrap_err();
+    Holder::reinit(&h, String::new()).unwrap_err();
+    assert_eq!(s, "ab");
+
+    let v = Viewer::new(String::from("c"));
+    let h = Holder::from(&v);
+    let v1 = v.clone();
+    let s = Viewer::leak(v);
+    drop(v1);
+    assert!(Holder::state(&h).is_frozen());
+    assert_state(Holder::state(&h), false, 1, 1, false);
+    Owner::try_from(&h).unwrap_err();
+    let v = Viewer::try_from(&h)?;
+    Viewer::try_unwrap(v).unwrap_err();
+    assert_eq!(s, "c");
+    Ok(())
+}
+
+#[test]
+fn test_handle() -> Result<(), State> {
+    fn len<H: ReadHandle<Target = str>>(handle: &H) -> usize {
+        ReadHandle::get(handle).len()
+    }
+
+    fn push<H: WriteHandle<Target = String>>(handle: &mut H) {
+        WriteHandle::get_mut(handle).push('b');
+    }
+
+    fn holder_count<H: Handle>(handle: &H) -> usize {
+        Handle::state(handle).holder_count()
+    }
+
+    let mut o = Owner::new(String::from("a"));
+    push(&mut o);
+    let h = Handle::holder(&o);
+    assert_eq!(holder_count(&o), 1);
+    assert!(Handle::ptr_eq(&o, &h));
+    let mut o = OwnerRef::from(o);
+    push(&mut o);
+    let v = ViewerRef::map(ViewerRef::from(o), String::as_str);
+    assert_eq!(len(&v), 3);
+    assert!(Handle::ptr_eq(&v, &h));
+    assert_eq!(Handle::addr(&v), Handle::addr(&h));
+    let v1 = Viewer::new(String::from("abc"));
+    assert!(!Handle::ptr_eq(&v, &v1));
+    assert!(Handle::same_allocation(&v, &h));
+    assert!(!Handle::same_allocation(&h, &v1));
+    assert_eq!(holder_count(&Handle::holder(&v1)), 1);
+    Ok(())
+}
+
+#[test]
+fn test_access() -> Result<(), State> {
+    let mut a = Access::new(String::from("a"));
+    assert_eq!(a.level(), AccessLevel::Owner);
+    a.try_get_mut()?.push('b');
+    let h = Holder::from(&a);
+    assert_state(Holder::state(&h), false, 1, 0, true);
+    Viewer::try_from(&h).unwrap_err();
+
+    a.downgrade_to_viewer()?;
+    assert_eq!(a.level(), AccessLevel::Viewer);
+    assert_state(Holder::state(&h), false, 1, 1, false);
+    a.try_get_mut().unwrap_err();
+    assert_eq!(a.try_get()?, "ab");
+
+    let v = Viewer::try_from(&h)?;
+    a.upgrade_to_owner().unwrap_err();
+    assert_eq!(a.level(), AccessLevel::Viewer);
+    drop(v);
+    a.upgrade_to_owner()?;
+    assert_state(Holder::state(&h), false, 1, 0, true);
+
+    a.downgrade_to_holder();
+    assert_eq!(a.level(), AccessLevel::Holder);
+    assert_state(Holder::state(&h), false, 2, 0, false);
+    a.try_get().unwrap_err();
+    let o = Owner::try_from(&h)?;
+    a.downgrade_to_viewer().unwrap_err();
+    a.upgrade_to_owner().unwrap_err();
+    assert_eq!(a.level(), AccessLevel::Holder);
+    Owner::drop_data(o);
+    a.upgrade_to_owner().unwrap_err();
+    drop(h);
+    assert_state(a.state(), true, 1, 0, false);
+
+    let f = Frozen::new(1);
+    let mut a = Access::from(Viewer::from(&f));
+    drop(f);
+    a.upgrade_to_owner().unwrap_err();
+    a.downgrade_to_holder();
+    a.downgrade_to_viewer()?;
+    assert_eq!(*a.try_get()?, 1);
+    Ok(())
+}
+
+#[test]
+fn test_identity() -> Result<(), State> {
+    let o = Owner::new((1, 2));
+    let h = Holder::from(&o);
+    let id = Handle::alloc_id(&o);
+    let o1 = OwnerRef::map(OwnerRef::from(o), |(_, b)| b);
+    assert_eq!(Handle::alloc_id(&o1), id);
+    let v = ViewerRef::from(o1);
+    assert!(Handle::ptr_eq(&v, &h));
+    drop(v);
+    let v = Viewer::try_from(&h)?;
+    assert!(Handle::ptr_eq(&v, &h));
+    assert_ne!(Handle::alloc_id(&Frozen::new((1, 2))), id);
+
+    let holders = [Holder::new(1), Holder::new(2), Holder::new(3)];
+    let mut map = BTreeMap::new();
+    for h in &holders {
+        map.insert(h.clone(), Handle::alloc_id(h));
+    }
+    let mut sorted = holders.clone();
+    sorted.sort();
+    assert!(map.keys().eq(sorted.iter()));
+    assert!(map.iter().all(|(h, id)| Handle::alloc_id(h) == *id));
+    let ids: BTreeMap<AllocId, Holder<i32>> =
+        holders.iter().map(|h| (Handle::alloc_id(h), h.clone())).collect();
+    assert_eq!(ids.len(), 3);
+
+    let c = CowViewer::from(Viewer::new(1));
+    assert_eq!(c, c.clone());
+    let a1 = Access::from(Holder::new(1));
+    let a2 = Access::from(Holder::new(1));
+    assert_ne!(a1, a2);
+    assert_eq!(a1.cmp(&a2), Handle::alloc_id(&a1).cmp(&Handle::alloc_id(&a2)));
+    let u1 = UniqueOwner::new(1);
+    let u2 = UniqueOwner::new(1);
+    assert_ne!(u1, u2);
+    Ok(())
+}
+
+#[test]
+fn test_forward() -> Result<(), State> {
+    use std::format;
+    use std::future;
+    use std::pin::Pin;
+    use std::task::Context;
+    use std::task::Poll;
+    use std::task::Waker;
+
+    let v = Viewer::new(String::from("a"));
+    assert_eq!(format!("{v}"), "a");
+    let r = ViewerRef::map(ViewerRef::from(v.clone()), String::as_str);
+    assert_eq!(AsRef::<str>::as_ref(&r), "a");
+
+    #[cfg(feature = "std")]
+    {
+        use std::io::Write;
+
+        fn write_to(mut w: impl Write) {
+            write!(w, "{}", 12).unwrap();
+            w.flush().unwrap();
+        }
+
+        let mut o = Owner::new(Vec::<u8>::new());
+        write_to(&mut o);
+        o[1] = b'3';
+        assert_eq!(o[..], *b"13");
+    }
+
+    let mut o = OwnerRef::from(Owner::new(vec![1, 2].into_iter()));
+    assert_eq!(o.size_hint(), (2, Some(2)));
+    assert_eq!(o.by_ref().sum::<i32>(), 3);
+
+    let mut o = Owner::new(future::ready(1));
+    let mut cx = Context::from_waker(Waker::noop());
+    assert_eq!(Pin::new(&mut o).poll(&mut cx), Poll::Ready(1));
+
+    let a = ByValue(Viewer::new(String::from("a")));
+    assert_eq!(a, ByValue(v));
+    assert_ne!(a, ByValue(Viewer::new(String::from("b"))));
+    assert!(a < ByValue(Viewer::new(String::from("b"))));
+    Ok(())
+}
+
+#[test]
+fn test_clone_data() -> Result<(), State> {
+    let v = Viewer::new((String::from("a"), 1));
+    let mut o = Viewer::clone_data(&v);
+    o.0.push('b');
+    assert_eq!(v.0, "a");
+    assert!(!Handle::ptr_eq(&o, &v));
+    assert_state(Owner::state(&o), false, 0, 0, true);
+
+    let r = ViewerRef::map(ViewerRef::from(v), |(s, _)| s);
+    let mut o1 = ViewerRef::to_owner(&r);
+    o1.push('c');
+    assert_eq!(*r, "a");
+    assert_eq!(*o1, "ac");
+
+    let h = Holder::from(o);
+    let h1 = Holder::try_clone_data(&h)?;
+    assert!(h1 != h);
+    assert_state(Holder::state(&h1), false, 1, 0, false);
+    let o = Owner::try_from(&h)?;
+    Holder::try_clone_data(&h).unwrap_err();
+    Owner::drop_data(o);
+    Holder::try_clone_data(&h).unwrap_err();
+    let v = Viewer::try_from(&h1)?;
+    assert_eq!(v.0, "ab");
+    Ok(())
+}
+
+#[test]
+fn test_cow_viewer() -> Result<(), State> {
+    let mut c = CowViewer::new(String::from("a"));
+    let id = Handle::alloc_id(&c);
+    CowViewer::make_mut(&mut c).push('b');
+    assert_eq!(Handle::alloc_id(&c), id);
+
+    let c1 = c.clone();
+    CowViewer::make_mut(&mut c).push('c');
+    assert_ne!(Handle::alloc_id(&c), id);
+    assert_eq!(*c, "abc");
+    assert_eq!(*c1, "ab");
+    assert_state(CowViewer::state(&c), false, 0, 1, false);
+    assert_state(CowViewer::state(&c1), false, 0, 1, false);
+
+    let h = Holder::from(&c);
+    let id = Handle::alloc_id(&c);
+    CowViewer::make_mut(&mut c).push('d');
+    assert_ne!(Handle::alloc_id(&c), id);
+    assert_eq!(*Viewer::try_from(&h)?, "abc");
+    drop(h);
+
+    let f = Frozen::new(1);
+    let mut c = CowViewer::from(Viewer::from(f));
+    *CowViewer::make_mut(&mut c) = 2;
+    let v = Viewer::from(c);
+    assert_eq!(*v, 2);
+    assert!(!Viewer::state(&v).is_frozen());
+    Ok(())
+}
+
+#[test]
+fn test_transaction() -> Result<(), State> {
+    let mut o = Owner::new(vec![1, 2]);
+    let r: Result<(), ()> = Owner::transaction(&mut o, |v| {
+        v.push(3);
+        Err(())
+    });
+    r.unwrap_err();
+    assert_eq!(*o, [1, 2]);
+    let r: Result<usize, ()> = Owner::transaction(&mut o, |v| {
+        v.push(3);
+        Ok(v.len())
+    });
+    assert_eq!(r, Ok(3));
+    assert_eq!(*o, [1, 2, 3]);
+
+    let mut o = OwnerRef::map(OwnerRef::from(o), |v| &mut v[..]);
+    let r: Result<(), &str> = OwnerRef::transaction_with_log(&mut o, |tx| {
+        for i in 0 .. 3 {
+            let old = tx[i];
+            tx[i] *= 10;
+            Transaction::log(tx, move |s: &mut [i32]| s[i] = old);
+            if i == 1 {
+                return Err("fail");
+            }
+        }
+        Ok(())
+    });
+    assert_eq!(r, Err("fail"));
+    assert_eq!(*o, [1, 2, 3]);
+
+    let mut o = Owner::from(o);
+    let r = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _: Result<(), ()> = Owner::transaction(&mut o, |v| {
+            v.clear();
+            panic!("transaction panic");
+        });
+    }));
+    r.unwrap_err();
+    assert_eq!(*o, [1, 2, 3]);
+    Ok(())
+}
+
+#[cfg(feature = "dirty")]
+#[test]
+fn test_history() -> Result<(), State> {
+    struct Delta(i32);
+
+    impl Revision<i32> for Delta {
+        type Base = i32;
+
+        fn base(data: &i32) -> i32 {
+            *data
+        }
+
+        fn diff(base: i32, data: &i32) -> Option<Self> {
+            if base == *data { None } else { Some(Delta(*data - base)) }
+        }
+
+        fn revert(self, data: &mut i32) -> Self {
+            *data -= self.0;
+            Delta(-self.0)
+        }
+    }
+
+    let mut history: History<String> = History::new(Holder::new(String::from("a")), 2);
+    history.edit()?.push('b');
+    history.edit()?.push('c');
+    history.edit()?.push('d');
+    assert_eq!(history.undo_count(), 2);
+    assert!(history.undo()?);
+    assert!(history.undo()?);
+    assert!(!history.undo()?);
+    assert_eq!(*Viewer::try_from(history.holder())?, "ab");
+    assert!(history.redo()?);
+    assert_eq!(history.redo_count(), 1);
+
+    let v = Viewer::try_from(history.holder())?;
+    assert_eq!(*v, "abc");
+    history.redo().unwrap_err();
+    history.edit().unwrap_err();
+    drop(v);
+    history.edit()?.push('e');
+    assert_eq!(history.redo_count(), 0);
+    assert!(!history.redo()?);
+
+    // an edit without mutable access isn't recorded
+    let undo_count = history.undo_count();
+    assert_eq!(history.edit()?.len(), 4);
+    assert_eq!(history.undo_count(), undo_count);
+    // an edit by another owner can't be reverted over
+    Owner::try_from(history.holder())?.push('f');
+    assert!(!history.undo()?);
+    assert_eq!(history.undo_count(), 0);
+    history.edit()?.push('g');
+    assert!(history.undo()?);
+    assert_eq!(*Viewer::try_from(history.holder())?, "abcef");
+    // data dropped and reinitialized by others can't be reverted over
+    history.edit()?.push('h');
+    Owner::drop_data(Owner::try_from(history.holder())?);
+    Holder::reinit(history.holder(), String::from("external"))?;
+    assert!(!history.undo()?);
+    assert_eq!(*Viewer::try_from(history.holder())?, "external");
+
+    let mut history: History<i32, Delta> = History::new(Holder::new(1), usize::MAX);
+    *history.edit()? += 2;
+    drop(history.edit()?);
+    *history.edit()? *= 2;
+    assert_eq!(history.undo_count(), 2);
+    history.undo()?;
+    assert_eq!(*Viewer::try_from(history.holder())?, 3);
+    history.undo()?;
+    assert_eq!(*Viewer::try_from(history.holder())?, 1);
+    history.redo()?;
+    history.redo()?;
+    assert_eq!(*Viewer::try_from(history.holder())?, 6);
+    Ok(())
+}
+
+#[cfg(feature = "subscribe")]
+#[test]
+fn test_subscribe() -> Result<(), State> {
+    let holder = Holder::new(vec![1]);
+    let changes = Rc::new(RefCell::new(Vec::new()));
+    let log = Rc::clone(&changes);
+    let viewed = Rc::new(Cell::new(0));
+    let len = Rc::clone(&viewed);
+    let h = Holder::clone(&holder);
+    let subscription = Holder::subscribe(&holder, move |change| {
+        log.borrow_mut().push(change);
+        if let Ok(viewer) = Viewer::try_from(&h) {
+            len.set(viewer.len());
+        }
+    });
+
+    Owner::try_from(subscription.holder())?.push(2);
+    assert_eq!(*changes.borrow(), [Change::Released]);
+    assert_eq!(viewed.get(), 2);
+
+    // nested acquisitions are released once
+    let owner = OwnerRef::from(Owner::try_from(subscription.holder())?);
+    let mut owner = OwnerRef::map(owner, |v| &mut v[0]);
+    *owner = 3;
+    let owner = Owner::from(owner);
+    let mut owner = UniqueOwner::try_from(owner).unwrap_err();
+    owner.push(4);
+    assert_eq!(changes.borrow().len(), 1);
+    let viewer = Viewer::from(owner);
+    assert_eq!(*changes.borrow(), [Change::Released; 2]);
+    assert_eq!(viewed.get(), 3);
+    drop(viewer);
+
+    Owner::drop_data(Owner::try_from(subscription.holder())?);
+    Holder::reinit(subscription.holder(), vec![5])?;
+    assert_eq!(changes.borrow()[2 ..], [Change::Dropped, Change::Reinit]);
+    assert_eq!(viewed.get(), 1);
+
+    drop(subscription);
+    Owner::try_from(&holder)?.push(6);
+    assert_eq!(changes.borrow().len(), 4);
+    Ok(())
+}
+
+#[cfg(feature = "dirty")]
+#[test]
+fn test_dirty() -> Result<(), State> {
+    let holder = Holder::new(vec![1, 2]);
+    assert!(!Holder::is_dirty(&holder));
+
+    let owner = Owner::try_from(&holder)?;
+    assert_eq!(owner.len(), 2);
+    drop(owner);
+    assert!(!Holder::is_dirty(&holder));
+    assert_eq!(Holder::modification_count(&holder), 0);
+
+    let mut owner = Owner::try_from(&holder)?;
+    owner.push(3);
+    owner[0] = 0;
+    assert!(Handle::is_dirty(&owner));
+    drop(owner);
+    assert!(Holder::is_dirty(&holder));
+    assert_eq!(Holder::modification_count(&holder), 2);
+
+    Holder::clear_dirty(&holder);
+    let viewer = Viewer::try_from(&holder)?;
+    assert!(!Handle::is_dirty(&viewer));
+    assert_eq!(Handle::modification_count(&viewer), 2);
+    drop(viewer);
+
+    let owner = OwnerRef::from(Owner::try_from(&holder)?);
+    let mut owner = OwnerRef::map(owner, |v| &mut v[1]);
+    *owner += 1;
+    drop(owner);
+    let mut access = Access::from(holder.clone());
+    access.upgrade_to_owner()?;
+    access.try_get_mut()?.clear();
+    drop(access);
+    assert!(Holder::is_dirty(&holder));
+    assert_eq!(Holder::modification_count(&holder), 5);
+
+    // mutable access in the map of OwnerRef
+    Holder::clear_dirty(&holder);
+    let owner = OwnerRef::from(Owner::try_from(&holder)?);
+    let owner = OwnerRef::map(owner, |v| {
+        v.push(7);
+        &mut v[0]
+    });
+    drop(owner);
+    assert!(Holder::is_dirty(&holder));
+    assert_eq!(Holder::modification_count(&holder), 6);
+
+    let mut viewer = CowViewer::new(1);
+    *CowViewer::make_mut(&mut viewer) += 1;
+    assert!(Handle::is_dirty(&viewer));
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::ops::Deref;
+use core::ops::DerefMut;
+
+// an entry of the undo log of a transaction, which restores a change of data
+pub trait Undo<D: ?Sized> {
+    fn undo(self, data: &mut D);
+}
+
+impl<D: ?Sized, F: FnOnce(&mut D)> Undo<D> for F {
+    fn undo(self, data: &mut D) {
+        self(data);
+    }
+}
+
+// data in a transaction, the undo log is applied in reverse order when the transaction fails
+pub struct Transaction<'a, D: ?Sized, U: Undo<D>> {
+    data: &'a mut D,
+    log: Vec<U>,
+    committed: bool,
+}
+
+// restore the snapshot when dropped without commit
+pub(crate) struct Restore<'a, D> {
+    data: &'a mut D,
+    snapshot: Option<D>,
+}
+
+impl<'a, D: ?Sized, U: Undo<D>> Transaction<'a, D, U> {
+    pub fn log(tx: &mut Self, undo: U) {
+        tx.log.push(undo);
+    }
+
+    pub(crate) fn run<R, E, F>(data: &'a mut D, f: F) -> Result<R, E>
+    where F: FnOnce(&mut Self) -> Result<R, E> {
+        let mut tx = Self { data, log: Vec::new(), committed: false };
+        let result = f(&mut tx);
+        tx.committed = result.is_ok();
+        result
+    }
+}
+
+impl<D: ?Sized, U: Undo<D>> Deref for Transaction<'_, D, U> {
+    type Target = D;
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl<D: ?Sized, U: Undo<D>> DerefMut for Transaction<'_, D, U> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.data
+    }
+}
+
+// also rollback when the transaction panics
+impl<D: ?Sized, U: Undo<D>> Drop for Transaction<'_, D, U> {
+    fn drop(&mut self) {
+        if self.committed {
+            return;
+        }
+        while let Some(undo) = self.log.pop() {
+            undo.undo(self.data);
+        }
+    }
+}
+
+impl<D: ?Sized, U: Undo<D>> Debug for Transaction<'_, D, U> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Transaction").field("log_len", &self.log.len()).finish()
+    }
+}
+
+impl<'a, D: Clone> Restore<'a, D> {
+    pub(crate) fn run<R, E, F>(data: &'a mut D, f: F) -> Result<R, E>
+    where F: FnOnce(&mut D) -> Result<R, E> {
+        let snapshot = Some(data.clone());
+        let mut snapshot = Restore { data, snapshot };
+        let result = f(snapshot.data);
+        if result.is_ok() {
+            snapshot.snapshot = None;
+        }
+        result
+    }
+}
+
+// also restore when the transaction panics
+impl<D> Drop for Restore<'_, D> {
+    fn drop(&mut self) {
+        if let Some(snapshot) = self.snapshot.take() {
+            *self.data = snapshot;
+        }
+    }
+}
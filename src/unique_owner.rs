@@ -0,0 +1,114 @@
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::mem;
+use core::ops::Deref;
+use core::ops::DerefMut;
+
+use crate::Owner;
+use crate::State;
+use crate::ptr::Ptr;
+
+pub struct UniqueOwner<D: ?Sized> {
+    ptr: Ptr<D>,
+}
+
+// SAFETY:
+// there is no other handle to the allocation, and no handle can be made without consuming self
+// so the state and the data can only be accessed from the thread that has self
+unsafe impl<D: ?Sized + Send> Send for UniqueOwner<D> {}
+
+impl<D: ?Sized> UniqueOwner<D> {
+    pub fn new(data: D) -> Self
+    where D: Sized {
+        Self { ptr: Ptr::new_owner(data) }
+    }
+
+    pub fn state(owner: &Self) -> State {
+        owner.ptr.cell().state()
+    }
+
+    pub fn move_data(owner: Self) -> D
+    where D: Sized {
+        Owner::move_data(Owner::from(owner))
+    }
+
+    pub(crate) fn ptr(owner: &Self) -> &Ptr<D> {
+        &owner.ptr
+    }
+}
+
+impl<D: ?Sized> Deref for UniqueOwner<D> {
+    type Target = D;
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: we have exclusive ref and data hasn't been dropped
+        unsafe { self.ptr.cell().deref() }
+    }
+}
+
+impl<D: ?Sized> DerefMut for UniqueOwner<D> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ptr.cell().modify();
+        // SAFETY: we have exclusive ref and data hasn't been dropped
+        unsafe { self.ptr.cell().deref_mut() }
+    }
+}
+
+impl<D: ?Sized> Drop for UniqueOwner<D> {
+    fn drop(&mut self) {
+        self.ptr.drop_from_owner();
+    }
+}
+
+impl<D: ?Sized> TryFrom<Owner<D>> for UniqueOwner<D> {
+    type Error = Owner<D>;
+    fn try_from(value: Owner<D>) -> Result<Self, Self::Error> {
+        let state = Owner::state(&value);
+        if state.holder_count() != 0 || state.is_local() {
+            return Err(value);
+        }
+        let ptr = Owner::ptr(&value).transfer();
+        mem::forget(value);
+        Ok(Self { ptr })
+    }
+}
+
+impl<D: ?Sized> Debug for UniqueOwner<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("UniqueOwner").field(&self.ptr).finish()
+    }
+}
+
+impl<D: Default> Default for UniqueOwner<D> {
+    fn default() -> Self {
+        Self::new(D::default())
+    }
+}
+
+impl<D: ?Sized> PartialEq for UniqueOwner<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr
+    }
+}
+
+impl<D: ?Sized> Eq for UniqueOwner<D> {}
+
+impl<D: ?Sized> Hash for UniqueOwner<D> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ptr.hash(state);
+    }
+}
+
+impl<D: ?Sized> PartialOrd for UniqueOwner<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<D: ?Sized> Ord for UniqueOwner<D> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ptr.cmp(&other.ptr)
+    }
+}
@@ -1,13 +1,21 @@
-use std::fmt::Debug;
-use std::fmt::Formatter;
-use std::hash::Hash;
-use std::hash::Hasher;
-use std::ops::Deref;
-
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::mem;
+use core::mem::ManuallyDrop;
+use core::ops::Deref;
+
+use allocator_api2::alloc::Allocator;
+
+use crate::CowViewer;
+use crate::Frozen;
 use crate::Holder;
 use crate::Owner;
 use crate::OwnerRef;
 use crate::State;
+use crate::UniqueOwner;
 use crate::ViewerRef;
 use crate::ptr::Ptr;
 
@@ -21,10 +29,93 @@ impl<D: ?Sized> Viewer<D> {
         Self { ptr: Ptr::new_viewer(data) }
     }
 
+    pub fn new_in<A: Allocator + Send + Sync + 'static>(data: D, alloc: A) -> Self
+    where D: Sized {
+        Self { ptr: Ptr::new_viewer_in(data, alloc, false) }
+    }
+
+    // the allocation can't be sent to another thread
+    pub(crate) fn new_local_in<A: Allocator + 'static>(data: D, alloc: A) -> Self
+    where D: Sized {
+        Self { ptr: Ptr::new_viewer_in(data, alloc, true) }
+    }
+
     pub fn state(viewer: &Self) -> State {
         viewer.ptr.cell().state()
     }
 
+    // clone the data into a new allocation
+    pub fn clone_data(viewer: &Self) -> Owner<D>
+    where D: Clone {
+        Owner::new(D::clone(viewer))
+    }
+
+    // succeed when it is the last handle
+    pub fn try_unwrap(viewer: Self) -> Result<D, Self>
+    where D: Sized {
+        let state = viewer.ptr.cell().state();
+        if state.holder_count() != 0 || state.viewer_count() != 1 {
+            return Err(viewer);
+        }
+        // SAFETY:
+        // we are the last handle
+        // we consume the Viewer when taking
+        // we change the state to dropped
+        // so we won't access the data anymore
+        Ok(unsafe { viewer.ptr.cell().move_data() })
+    }
+
+    // the data is frozen and viewed forever, so holders can only view it
+    pub fn leak(viewer: Self) -> &'static D
+    where D: 'static {
+        viewer.ptr.freeze();
+        // SAFETY: we never release the viewer count and frozen data can't be owned
+        let data = unsafe { viewer.ptr.cell().deref() };
+        mem::forget(viewer);
+        data
+    }
+
+    pub fn into_raw(viewer: Self) -> *const D {
+        let data = viewer.ptr.cell().data_ptr();
+        mem::forget(viewer);
+        data
+    }
+
+    /// # Safety
+    ///
+    /// `data` must be returned by `Viewer::into_raw`, and one viewer count of the `StateCell`
+    /// is taken over by the returned `Viewer`, so it must be called only once for each count.
+    pub unsafe fn from_raw(data: *const D) -> Self {
+        // SAFETY: data is viewed by us and hasn't been dropped
+        let align = align_of_val(unsafe { &*data });
+        // SAFETY: data is the data ptr of a StateCell
+        Self { ptr: unsafe { Ptr::from_data_ptr(data, align) } }
+    }
+
+    pub fn as_ptr(viewer: &Self) -> *const D {
+        viewer.ptr.cell().data_ptr()
+    }
+
+    /// # Safety
+    ///
+    /// `data` must be returned by `Viewer::into_raw`, and there must be a viewer count of the `StateCell`
+    /// which is not released during this call. The new viewer count should be released by `Viewer::from_raw`
+    /// or `Viewer::decrement_count`.
+    pub unsafe fn increment_count(data: *const D) {
+        // SAFETY: the safety requirements are the same
+        let viewer = ManuallyDrop::new(unsafe { Self::from_raw(data) });
+        mem::forget(Self::clone(&viewer));
+    }
+
+    /// # Safety
+    ///
+    /// `data` must be returned by `Viewer::into_raw`, and one viewer count of the `StateCell` is released,
+    /// so it must be called only once for each count.
+    pub unsafe fn decrement_count(data: *const D) {
+        // SAFETY: the safety requirements are the same
+        drop(unsafe { Self::from_raw(data) });
+    }
+
     pub(crate) fn ptr(viewer: &Self) -> &Ptr<D> {
         &viewer.ptr
     }
@@ -64,10 +155,18 @@ impl<D: ?Sized> TryFrom<Holder<D>> for Viewer<D> {
     }
 }
 
+impl<D: ?Sized> From<CowViewer<D>> for Viewer<D> {
+    fn from(value: CowViewer<D>) -> Self {
+        Self { ptr: CowViewer::ptr(&value).clone_to_viewer().unwrap() }
+    }
+}
+
 impl<D: ?Sized> From<Owner<D>> for Viewer<D> {
     fn from(value: Owner<D>) -> Self {
-        let holder = Holder::from(value);
-        Self { ptr: Holder::ptr(&holder).clone_to_viewer().unwrap() }
+        let ptr = Owner::ptr(&value).transfer();
+        mem::forget(value);
+        ptr.cell().owner_to_viewer();
+        Self { ptr }
     }
 }
 
@@ -85,13 +184,36 @@ impl<Source: ?Sized, Target: ?Sized> From<ViewerRef<Source, Target>> for Viewer<
 
 impl<Source: ?Sized, Target: ?Sized> From<OwnerRef<Source, Target>> for Viewer<Source> {
     fn from(value: OwnerRef<Source, Target>) -> Self {
-        let holder = Holder::from(value);
-        Self { ptr: Holder::ptr(&holder).clone_to_viewer().unwrap() }
+        let ptr = OwnerRef::source(&value).transfer();
+        mem::forget(value);
+        ptr.cell().owner_to_viewer();
+        Self { ptr }
+    }
+}
+
+impl<D: ?Sized> From<&Frozen<D>> for Viewer<D> {
+    fn from(value: &Frozen<D>) -> Self {
+        Self { ptr: Frozen::ptr(value).clone_to_frozen() }
+    }
+}
+
+impl<D: ?Sized> From<Frozen<D>> for Viewer<D> {
+    fn from(value: Frozen<D>) -> Self {
+        Self { ptr: Frozen::ptr(&value).clone_to_frozen() }
+    }
+}
+
+impl<D: ?Sized> From<UniqueOwner<D>> for Viewer<D> {
+    fn from(value: UniqueOwner<D>) -> Self {
+        let ptr = UniqueOwner::ptr(&value).transfer();
+        mem::forget(value);
+        ptr.cell().owner_to_viewer();
+        Self { ptr }
     }
 }
 
 impl<D: ?Sized> Debug for Viewer<D> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_tuple("Viewer").field(&self.ptr).finish()
     }
 }
@@ -115,3 +237,15 @@ impl<D: ?Sized> Hash for Viewer<D> {
         self.ptr.hash(state);
     }
 }
+
+impl<D: ?Sized> PartialOrd for Viewer<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<D: ?Sized> Ord for Viewer<D> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ptr.cmp(&other.ptr)
+    }
+}
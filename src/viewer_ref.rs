@@ -1,8 +1,10 @@
-use std::fmt::Debug;
-use std::fmt::Formatter;
-use std::hash::Hash;
-use std::hash::Hasher;
-use std::ops::Deref;
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::mem;
+use core::ops::Deref;
 
 use crate::Holder;
 use crate::Owner;
@@ -43,6 +45,12 @@ impl<Source: ?Sized, Target: ?Sized> ViewerRef<Source, Target> {
         Ok(ViewerRef { ref_: Ref::new(source, target) })
     }
 
+    // clone the target into a new allocation
+    pub fn to_owner(viewer: &Self) -> Owner<Target>
+    where Target: Clone {
+        Owner::new(Target::clone(viewer))
+    }
+
     pub(crate) fn source(viewer: &Self) -> &Ptr<Source> {
         viewer.ref_.source()
     }
@@ -102,8 +110,9 @@ impl<Source: ?Sized> From<Viewer<Source>> for ViewerRef<Source, Source> {
 
 impl<Source: ?Sized> From<Owner<Source>> for ViewerRef<Source, Source> {
     fn from(value: Owner<Source>) -> Self {
-        let holder = Holder::from(value);
-        let source = Holder::ptr(&holder).clone_to_viewer().unwrap();
+        let source = Owner::ptr(&value).transfer();
+        mem::forget(value);
+        source.cell().owner_to_viewer();
         Self { ref_: Ref::from_source(source) }
     }
 }
@@ -111,14 +120,15 @@ impl<Source: ?Sized> From<Owner<Source>> for ViewerRef<Source, Source> {
 impl<Source: ?Sized, Target: ?Sized> From<OwnerRef<Source, Target>> for ViewerRef<Source, Target> {
     fn from(value: OwnerRef<Source, Target>) -> Self {
         let target = OwnerRef::target(&value);
-        let holder = Holder::from(value);
-        let source = Holder::ptr(&holder).clone_to_viewer().unwrap();
+        let source = OwnerRef::source(&value).transfer();
+        mem::forget(value);
+        source.cell().owner_to_viewer();
         ViewerRef { ref_: Ref::new(source, target) }
     }
 }
 
 impl<Source: ?Sized, Target: ?Sized> Debug for ViewerRef<Source, Target> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.write_str("Viewer")?;
         self.ref_.fmt(f)
     }
@@ -137,3 +147,15 @@ impl<Source: ?Sized, Target: ?Sized> Hash for ViewerRef<Source, Target> {
         self.ref_.hash(state);
     }
 }
+
+impl<Source: ?Sized, Target: ?Sized> PartialOrd for ViewerRef<Source, Target> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Source: ?Sized, Target: ?Sized> Ord for ViewerRef<Source, Target> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ref_.cmp(&other.ref_)
+    }
+}